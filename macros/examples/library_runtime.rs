@@ -0,0 +1,108 @@
+// Runtimes defined in a library crate, which other crates can use. The pallet macros expect
+// `support` at the root of the crate which uses them, so the library re-exports it there, while the
+// runtime macro is given its path. Both runtimes share a module, each with its own `Call` and
+// `Event` enums. This is built as a `lib`, since the UI tests can only build binaries.
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+pub use common::{support, system};
+
+pub mod types {
+	pub type AccountId = String;
+	pub type Header = crate::support::Header<u32>;
+	pub type Block<Call> = crate::support::Block<Header, crate::support::Extrinsic<AccountId, Call>>;
+}
+
+pub mod counter {
+	use crate::{support, system};
+	use support::{storage::StorageMap, DispatchResult, TakeEvents};
+
+	pub trait Config: system::Config {}
+
+	/// The events emitted by the counter pallet.
+	#[derive(Debug, Clone, PartialEq, macros::EventMetadata)]
+	pub enum Event<T: Config> {
+		/// The counter of `who` was increased to `value`.
+		Incremented { who: T::AccountId, value: u32 },
+	}
+
+	/// The errors returned by the counter pallet.
+	#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+	pub enum Error {
+		/// The counter would overflow.
+		#[error("Overflow")]
+		Overflow,
+	}
+
+	#[macros::storage]
+	#[derive(Debug, Clone, PartialEq)]
+	pub struct Pallet<T: Config> {
+		/// The counter of each account.
+		#[storage(getter = counter, setter = set_counter)]
+		counters: StorageMap<T::AccountId, u32>,
+		events: Vec<Event<T>>,
+	}
+
+	#[macros::call(error = Error)]
+	impl<T: Config> Pallet<T> {
+		/// Increase the counter of the caller by one.
+		pub fn increment(&mut self, caller: T::AccountId) -> DispatchResult {
+			let value = self.counter(&caller).checked_add(1).ok_or(Error::Overflow)?;
+			self.set_counter(caller.clone(), value);
+			self.events.push(Event::Incremented { who: caller, value });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> support::Hooks<T> for Pallet<T> {}
+
+	impl<T: Config> TakeEvents for Pallet<T> {
+		type Event = Event<T>;
+
+		fn take_events(&mut self) -> Vec<Event<T>> {
+			core::mem::take(&mut self.events)
+		}
+	}
+}
+
+pub mod runtime {
+	use crate::{counter, system, types};
+
+	impl system::Config for Chain {
+		type AccountId = types::AccountId;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ChainEvent;
+	}
+
+	impl counter::Config for Chain {}
+
+	#[derive(Debug, Clone, PartialEq)]
+	#[macros::runtime(
+		block = types::Block<ChainCall>,
+		system = system,
+		support = crate::common::support,
+	)]
+	pub struct Chain {
+		pub system: system::Pallet<Chain>,
+		pub counter: counter::Pallet<Chain>,
+	}
+
+	// A second runtime without the counter pallet.
+	impl system::Config for TestChain {
+		type AccountId = types::AccountId;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = TestChainEvent;
+	}
+
+	#[derive(Debug, Clone, PartialEq)]
+	#[macros::runtime(
+		block = types::Block<TestChainCall>,
+		system = system,
+		support = crate::common::support,
+	)]
+	pub struct TestChain {
+		pub system: system::Pallet<TestChain>,
+	}
+}
@@ -3,7 +3,21 @@ use quote::quote;
 
 /// See the `fn call` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
-	let CallDef { pallet_struct, methods } = def;
+	let CallDef { pallet_struct, generics, methods, error } = def;
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	// The `Call` enum has the generic parameters of the impl block, without its where clause. Like
+	// the pallet struct, it defaults its instance parameter, which is any parameter other than `T`,
+	// to `()`.
+	let mut call_generics = generics.clone();
+	call_generics.where_clause = None;
+	let type_params = call_generics.type_params().map(|param| param.ident.clone()).collect::<Vec<_>>();
+	let mut call_decl_generics = call_generics.clone();
+	for param in call_decl_generics.type_params_mut().filter(|param| param.ident != "T") {
+		param.eq_token = Some(Default::default());
+		param.default = Some(syn::parse_quote!(()));
+	}
+	let (call_impl_generics, _, _) = call_generics.split_for_impl();
 
 	// This is a vector of all the callable function names.
 	let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
@@ -23,6 +37,94 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// Calls which asked for `runtime: &mut T` are handed the runtime instead of `self`, and reach
+	// their pallet through it. The other calls are made on the pallet, borrowed from the runtime.
+	// Note that we assume the `caller` is the first argument of every call, after `self` or
+	// `runtime`.
+	let call_fn = methods
+		.iter()
+		.zip(&args_name)
+		.map(|(method, args_name)| {
+			let name = &method.name;
+			if method.with_runtime {
+				quote!(Self::#name(runtime, caller, #( #args_name ),*))
+			} else {
+				quote! {
+					crate::support::HasPallet::<Self>::pallet_mut(runtime).#name(caller, #( #args_name ),*)
+				}
+			}
+		})
+		.collect::<Vec<_>>();
+
+	// Calls made on the pallet borrow it from the runtime.
+	let mut dispatch_generics = generics.clone();
+	dispatch_generics
+		.make_where_clause()
+		.predicates
+		.push(syn::parse_quote!(T: crate::support::HasPallet<#pallet_struct #ty_generics>));
+	let dispatch_where_clause = &dispatch_generics.where_clause;
+
+	// The index of each call, as given by `#[call_index(..)]`.
+	let call_index = methods.iter().map(|method| method.index).collect::<Vec<_>>();
+
+	// Calls without a `#[weight(..)]` attribute get the default weight.
+	let weight = methods
+		.iter()
+		.map(|method| match &method.weight {
+			Some(weight) => quote!(#weight),
+			None => quote!(crate::support::DEFAULT_WEIGHT),
+		})
+		.collect::<Vec<_>>();
+
+	// Every argument type of every call, which the impls of the common traits on `Call` are bounded
+	// on, since `T` itself does not implement them.
+	let all_args_type = args_type.iter().flatten().collect::<Vec<_>>();
+
+	// The arguments of each call bound under other names, to compare them with the arguments of
+	// `self`.
+	let other_args_name = args_name
+		.iter()
+		.map(|names| names.iter().map(|name| quote::format_ident!("other_{}", name)).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+
+	// The label written before each argument when displaying a call, separated by commas.
+	let args_label = args_name
+		.iter()
+		.map(|names| {
+			let labels = names.iter().enumerate().map(|(i, name)| {
+				if i == 0 { name.to_string() } else { format!(", {}", name) }
+			});
+			labels.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+
+	// The descriptions of the calls, for the metadata. The arguments are described both as they are
+	// written, and as the concrete types they resolve to in the runtime.
+	let call_metadata = methods.iter().map(|method| {
+		let name = method.name.to_string();
+		let index = method.index;
+		let docs = &method.docs;
+		let args = method.args.iter().map(|(arg_name, arg_type)| {
+			let arg_name = arg_name.to_string();
+			let description = crate::metadata::type_description(arg_type);
+			quote! {
+				crate::support::metadata::FieldMetadata {
+					name: #arg_name,
+					ty: #description,
+					type_name: core::any::type_name::<#arg_type>(),
+				}
+			}
+		});
+		quote! {
+			crate::support::metadata::CallMetadata {
+				name: #name,
+				index: #index,
+				args: vec![ #( #args ),* ],
+				docs: vec![ #( #docs ),* ],
+			}
+		}
+	});
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
 	// and the `Dispatch` trait logic to route a `caller` to access those functions.
 	let dispatch_impl = quote! {
@@ -30,35 +132,185 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
-		pub enum Call<T: Config> {
+		pub enum Call #call_decl_generics {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
+			// This variant can never be built, and only uses the generic parameters, which the
+			// arguments of the calls may not use.
+			#[doc(hidden)]
+			__Ignore(core::marker::PhantomData<( #( #type_params, )* )>, core::convert::Infallible),
 		}
 
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
 		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
+		impl #impl_generics crate::support::PalletDispatch<T> for #pallet_struct #ty_generics #dispatch_where_clause {
 			type Caller = T::AccountId;
-			type Call = Call<T>;
+			type Call = Call #ty_generics;
 
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
+			#[allow(unused_variables)]
+			fn dispatch(
+				runtime: &mut T,
+				caller: Self::Caller,
+				call: Self::Call,
+			) -> crate::support::DispatchResult {
 				match call {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
-							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
-								caller,
-								#( #args_name ),*
-							)?;
+							#call_fn?;
 						},
 					)*
+					Call::__Ignore(_, never) => match never {},
 				}
 				Ok(())
 			}
 		}
+
+		impl #call_impl_generics Call #ty_generics {
+			// The stable index of the call within the pallet, which does not depend on the order
+			// of the functions.
+			pub fn call_index(&self) -> u32 {
+				match self {
+					#(
+						Call::#fn_name { .. } => #call_index,
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		// The weight of each call, computed from references to its arguments.
+		impl #impl_generics crate::support::GetWeight for Call #ty_generics #where_clause {
+			#[allow(unused_variables)]
+			fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => #weight,
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+	};
+
+	// This quote block implements the common traits on `Call`, bounded on the types of the arguments
+	// rather than on `T` like `#[derive]` would.
+	let traits_impl = quote! {
+		impl #call_impl_generics Clone for Call #ty_generics where #( #all_args_type: Clone, )* {
+			fn clone(&self) -> Self {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => Call::#fn_name {
+							#( #args_name: Clone::clone(#args_name) ),*
+						},
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		impl #call_impl_generics core::fmt::Debug for Call #ty_generics where #( #all_args_type: core::fmt::Debug, )* {
+			fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => f
+							.debug_struct(stringify!(#fn_name))
+							#( .field(stringify!(#args_name), #args_name) )*
+							.finish(),
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		impl #call_impl_generics PartialEq for Call #ty_generics where #( #all_args_type: PartialEq, )* {
+			#[allow(unreachable_patterns)]
+			fn eq(&self, other: &Self) -> bool {
+				match (self, other) {
+					#(
+						(
+							Call::#fn_name { #( #args_name ),* },
+							Call::#fn_name { #( #args_name: #other_args_name ),* },
+						) => true #( && #args_name == #other_args_name )*,
+					)*
+					(Call::__Ignore(_, never), _) => match *never {},
+					_ => false,
+				}
+			}
+		}
+
+		impl #call_impl_generics Eq for Call #ty_generics where #( #all_args_type: Eq, )* {}
+
+		// Display the call like `transfer(to: dayitva, amount: 30)`.
+		impl #call_impl_generics core::fmt::Display for Call #ty_generics
+		where
+			#( #all_args_type: crate::support::DisplayArg, )*
+		{
+			fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => {
+							write!(f, "{}(", stringify!(#fn_name))?;
+							#(
+								write!(f, "{}: ", #args_label)?;
+								crate::support::DisplayArg::fmt_arg(#args_name, f)?;
+							)*
+							write!(f, ")")
+						},
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		// Parse a call written like `transfer(to="bob", amount=10)`, where the arguments are
+		// named and can be given in any order.
+		impl #call_impl_generics core::str::FromStr for Call #ty_generics
+		where
+			#( #all_args_type: crate::support::parse::ParseArg, )*
+		{
+			type Err = String;
+
+			fn from_str(s: &str) -> Result<Self, String> {
+				#[allow(unused_mut)]
+				let (name, mut args) = crate::support::parse::CallArgs::parse(s)?;
+				let call = match name {
+					#(
+						stringify!(#fn_name) => Call::#fn_name {
+							#( #args_name: args.take(stringify!(#args_name))? ),*
+						},
+					)*
+					_ => return Err(format!("unknown call `{}`", name)),
+				};
+				args.finish()?;
+				Ok(call)
+			}
+		}
+	};
+
+	// The errors are the ones declared by the error type of the pallet, if it has one.
+	let errors = match &error {
+		Some(error) => quote! { <#error as crate::support::metadata::GetErrorMetadata>::error_metadata() },
+		None => quote! { vec![] },
+	};
+
+	// This quote block describes the calls and errors of the pallet.
+	let metadata_impl = quote! {
+		impl #impl_generics crate::support::metadata::GetCallMetadata for Call #ty_generics #where_clause {
+			fn call_metadata() -> Vec<crate::support::metadata::CallMetadata> {
+				vec![ #( #call_metadata ),* ]
+			}
+
+			fn error_metadata() -> Vec<&'static str> {
+				#errors
+			}
+		}
 	};
 
 	// Return the generated code.
-	dispatch_impl.into()
+	quote! {
+		#dispatch_impl
+		#traits_impl
+		#metadata_impl
+	}
 }
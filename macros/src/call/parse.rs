@@ -5,6 +5,45 @@ use syn::spanned::Spanned;
 mod keyword {
 	syn::custom_keyword!(T);
 	syn::custom_keyword!(AccountId);
+	syn::custom_keyword!(explicit_call_index);
+	syn::custom_keyword!(error);
+}
+
+/// The helper attributes which can be placed on the functions of the annotated impl block, and
+/// are removed by the macro.
+const HELPER_ATTRS: &[&str] = &["weight", "call_index", "skip_call"];
+
+/// The arguments given to the macro, as in `#[macros::call(explicit_call_index, error = Error)]`.
+#[derive(Debug, Default)]
+pub struct CallArgs {
+	/// Whether every call must be given an index with `#[call_index(..)]`.
+	pub explicit_call_index: bool,
+	/// The type of the errors of the pallet, given with `error = ..`.
+	pub error: Option<syn::Path>,
+}
+
+impl syn::parse::Parse for CallArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut args = Self::default();
+		while !input.is_empty() {
+			let lookahead = input.lookahead1();
+			if lookahead.peek(keyword::explicit_call_index) {
+				input.parse::<keyword::explicit_call_index>()?;
+				args.explicit_call_index = true;
+			} else if lookahead.peek(keyword::error) {
+				input.parse::<keyword::error>()?;
+				input.parse::<syn::Token![=]>()?;
+				args.error = Some(input.parse()?);
+			} else {
+				return Err(lookahead.error())
+			}
+
+			if !input.is_empty() {
+				input.parse::<syn::Token![,]>()?;
+			}
+		}
+		Ok(args)
+	}
 }
 
 /// This object will collect all the information we need to keep while parsing the callable
@@ -14,8 +53,13 @@ pub struct CallDef {
 	/// This is the name of the pallet struct where the callable functions are implemented. We
 	/// mostly assume it is `Pallet`.
 	pub pallet_struct: syn::Ident,
+	/// The generics and where clause of the `impl` block, which are reused on the generated
+	/// `PalletDispatch` implementation.
+	pub generics: syn::Generics,
 	/// This is a list of the callable functions exposed by this pallet. See `CallVariantDef`.
 	pub methods: Vec<CallVariantDef>,
+	/// The type of the errors of the pallet, which are listed in the metadata.
+	pub error: Option<syn::Path>,
 }
 
 /// This is the metadata we keep about each callable function in our pallet.
@@ -23,12 +67,20 @@ pub struct CallDef {
 pub struct CallVariantDef {
 	/// The function name.
 	pub name: syn::Ident,
+	/// The index of the call, given with `#[call_index(..)]` or following the previous call.
+	pub index: u32,
+	/// Whether the function takes a `runtime: &mut T` argument instead of `self`.
+	pub with_runtime: bool,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// The expression given with `#[weight(..)]`, if any.
+	pub weight: Option<syn::Expr>,
+	/// The doc comments of the function.
+	pub docs: Vec<String>,
 }
 
 impl CallDef {
-	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+	pub fn try_from(args: CallArgs, item: &mut syn::Item) -> syn::Result<Self> {
 		// First we check that we are parsing an `impl`.
 		let item_impl = if let syn::Item::Impl(item) = item {
 			item
@@ -39,42 +91,103 @@ impl CallDef {
 		// Extract the name of the struct. We mostly assume it is `Pallet`, but we can handle it
 		// when it isn't.
 		let pallet_struct = match &*item_impl.self_ty {
-			syn::Type::Path(tp) => tp.path.segments.first().unwrap().ident.clone(),
-			_ => panic!("not supported tokens"),
+			syn::Type::Path(tp) if tp.qself.is_none() => tp.path.segments.last().map(|s| s.ident.clone()),
+			_ => None,
 		};
+		let pallet_struct = pallet_struct.ok_or_else(|| {
+			let msg = "Invalid pallet::call, expected the pallet struct, like `Pallet<T>`";
+			syn::Error::new(item_impl.self_ty.span(), msg)
+		})?;
 
 		// Here is where we will store all the callable functions.
 		let mut methods = vec![];
-		for item in item_impl.items {
+		for item in item_impl.items.iter_mut() {
 			if let syn::ImplItem::Fn(method) = item {
+				// Functions marked with `#[skip_call]` are helpers, which are not callable.
+				if take_attr(&mut method.attrs, "skip_call")?.is_some() {
+					continue
+				}
+
+				let weight = match take_attr(&mut method.attrs, "weight")? {
+					Some(attr) => Some(attr.parse_args::<syn::Expr>()?),
+					None => None,
+				};
+
+				// Like the discriminants of an enum, a call without an explicit index follows the
+				// previous call.
+				let index = match take_attr(&mut method.attrs, "call_index")? {
+					Some(attr) => {
+						let lit = attr.parse_args::<syn::LitInt>()?;
+						let index = lit.base10_parse::<u32>()?;
+						if let Some(other) = methods.iter().find(|m: &&CallVariantDef| m.index == index) {
+							let msg = format!(
+								"Invalid call, index {} is already used by `{}`",
+								index, other.name
+							);
+							return Err(syn::Error::new(lit.span(), msg))
+						}
+						index
+					},
+					None if args.explicit_call_index => {
+						let msg = "Invalid call, expected `#[call_index(..)]` attribute";
+						return Err(syn::Error::new(method.sig.ident.span(), msg))
+					},
+					None => {
+						let index = match methods.last() {
+							Some(previous) => previous.index.checked_add(1).ok_or_else(|| {
+								let msg = "Invalid call, call index overflow";
+								syn::Error::new(method.sig.ident.span(), msg)
+							})?,
+							None => 0,
+						};
+						if let Some(other) = methods.iter().find(|m| m.index == index) {
+							let msg = format!(
+								"Invalid call, implicit index {} is already used by `{}`",
+								index, other.name
+							);
+							return Err(syn::Error::new(method.sig.ident.span(), msg))
+						}
+						index
+					},
+				};
+
 				// Here is where we will store all the args for each callable functions.
 				let mut args = vec![];
 
-				// First argument should be some variant of `self`.
-				match method.sig.inputs.first() {
-					Some(syn::FnArg::Receiver(_)) => {},
+				// First argument should be some variant of `self`, or `runtime: &mut T` for calls
+				// which need access to the rest of the runtime. Those calls reach their own pallet
+				// through the runtime as well, so they can not also take `self`.
+				let with_runtime = match method.sig.inputs.first() {
+					Some(syn::FnArg::Receiver(_)) => false,
+					Some(syn::FnArg::Typed(arg)) if is_runtime_arg(arg) => true,
 					_ => {
-						let msg = "Invalid call, first argument must be a variant of self";
-						return Err(syn::Error::new(method.sig.span(), msg))
+						let msg = "Invalid call, first argument must be a variant of self, or `runtime: &mut T`";
+						return Err(syn::Error::new(method.sig.ident.span(), msg))
 					},
+				};
+				if let Some(syn::FnArg::Typed(arg)) = method.sig.inputs.iter().nth(1) {
+					if !with_runtime && is_runtime_arg(arg) {
+						let msg = "Invalid call, a call taking `runtime: &mut T` reaches its pallet through the runtime, so it can not take self";
+						return Err(syn::Error::new(arg.span(), msg))
+					}
 				}
 
-				// The second argument should be the `caller: T::AccountId` argument.
-				match method.sig.inputs.iter().skip(1).next() {
+				// The next argument should be the `caller: T::AccountId` argument.
+				match method.sig.inputs.iter().nth(1) {
 					Some(syn::FnArg::Typed(arg)) => {
 						// Here we specifically check that this argument is as we expect for
 						// `caller: T::AccountId`.
 						check_caller_arg(arg)?;
 					},
 					_ => {
-						let msg = "Invalid call, second argument should be `caller: T::AccountId`";
-						return Err(syn::Error::new(method.sig.span(), msg))
+						let msg = "Invalid call, expected argument `caller: T::AccountId`";
+						return Err(syn::Error::new(method.sig.ident.span(), msg))
 					},
 				}
 
 				let fn_name = method.sig.ident.clone();
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
+				// Parsing the rest of the args. Skipping `self` or `runtime`, and `caller`.
 				for arg in method.sig.inputs.iter().skip(2) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
@@ -83,9 +196,14 @@ impl CallDef {
 						unreachable!("All args should be typed.");
 					};
 
-					// Extract the name of the argument.
+					// Extract the name of the argument. Like `_caller`, an argument which is unused by
+					// the function can start with an underscore, which is not part of its name in
+					// the `Call` enum.
 					let arg_ident = if let syn::Pat::Ident(pat) = &*arg.pat {
-						pat.ident.clone()
+						match pat.ident.to_string().strip_prefix('_') {
+							Some(name) if !name.is_empty() => syn::Ident::new(name, pat.ident.span()),
+							_ => pat.ident.clone(),
+						}
 					} else {
 						let msg = "Invalid pallet::call, argument must be ident";
 						return Err(syn::Error::new(arg.pat.span(), msg))
@@ -96,15 +214,60 @@ impl CallDef {
 				}
 
 				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				let docs = crate::metadata::docs(&method.attrs);
+				methods.push(CallVariantDef { name: fn_name, index, with_runtime, args, weight, docs });
 			}
 		}
 
 		// Return all callable functions for this pallet.
-		Ok(Self { pallet_struct, methods })
+		Ok(Self { pallet_struct, generics: item_impl.generics.clone(), methods, error: args.error })
+	}
+}
+
+/// Remove the helper attribute `name` from the attributes of a call, and return it.
+fn take_attr(attrs: &mut Vec<syn::Attribute>, name: &str) -> syn::Result<Option<syn::Attribute>> {
+	let mut found = None;
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+		if found.is_some() {
+			let msg = format!("Invalid call, duplicate `#[{}]` attribute", name);
+			return Err(syn::Error::new(attr.span(), msg))
+		}
+		found = Some(attr.clone());
+	}
+	attrs.retain(|attr| !attr.path().is_ident(name));
+	Ok(found)
+}
+
+/// Remove all the helper attributes from the functions of the annotated impl block, so that the
+/// impl block can still be emitted when parsing it failed.
+pub fn remove_helper_attrs(item: &mut syn::Item) {
+	if let syn::Item::Impl(item_impl) = item {
+		for item in item_impl.items.iter_mut() {
+			if let syn::ImplItem::Fn(method) = item {
+				method
+					.attrs
+					.retain(|attr| !HELPER_ATTRS.iter().any(|name| attr.path().is_ident(name)));
+			}
+		}
 	}
 }
 
+/// Check if the arg is exactly `runtime: &mut T` (or `_runtime: &mut T`).
+pub fn is_runtime_arg(arg: &syn::PatType) -> bool {
+	let name_matches = match &*arg.pat {
+		syn::Pat::Ident(ident) => ident.ident == "runtime" || ident.ident == "_runtime",
+		_ => false,
+	};
+	let type_matches = match &*arg.ty {
+		syn::Type::Reference(reference) => {
+			reference.mutability.is_some() &&
+				matches!(&*reference.elem, syn::Type::Path(tp) if tp.path.is_ident("T"))
+		},
+		_ => false,
+	};
+	name_matches && type_matches
+}
+
 /// Check caller arg is exactly: `caller: T::AccountId`.
 ///
 /// This is kept strict to keep the code simple.
@@ -123,18 +286,16 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
 	if let syn::Pat::Ident(ident) = &*arg.pat {
 		// We also support the name as `_caller` for when the variable is unused.
 		if &ident.ident != "caller" && &ident.ident != "_caller" {
-			let msg = "Invalid name for second parameter: expected `caller: T::AccountId`";
+			let msg = "Invalid name for caller parameter: expected `caller: T::AccountId`";
 			return Err(syn::Error::new(ident.span(), msg))
 		}
 	}
 
 	// This checks the type is `T::AccountId` with `CheckDispatchableFirstArg`
 	let ty = &arg.ty;
-	syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map_err(|e| {
-		let msg = "Invalid type for second parameter: expected `caller: T::AccountId`";
-		let mut err = syn::Error::new(ty.span(), msg);
-		err.combine(e);
-		err
+	syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map_err(|_| {
+		let msg = "Invalid type for caller parameter: expected `caller: T::AccountId`";
+		syn::Error::new(ty.span(), msg)
 	})?;
 
 	Ok(())
@@ -0,0 +1,64 @@
+use quote::quote;
+use syn::spanned::Spanned;
+
+/// See the `fn error` docs at the `lib.rs` of this crate for a high level definition.
+pub fn error(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let item = syn::parse_macro_input!(item as syn::DeriveInput);
+	match expand_error(item) {
+		Ok(generated) => generated.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+fn expand_error(item: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+	let data = if let syn::Data::Enum(data) = &item.data {
+		data
+	} else {
+		return Err(syn::Error::new(item.span(), "Invalid error, expected an enum"))
+	};
+
+	let error_enum = &item.ident;
+	let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+	// Each variant must be a unit variant with its message given by `#[error("..")]`.
+	let mut variants = vec![];
+	let mut messages = vec![];
+	for variant in &data.variants {
+		if !matches!(variant.fields, syn::Fields::Unit) {
+			let msg = "Invalid error, expected a variant without fields";
+			return Err(syn::Error::new(variant.fields.span(), msg))
+		}
+
+		let mut message = None;
+		for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("error")) {
+			if message.is_some() {
+				let msg = "Invalid error, duplicate `#[error]` attribute";
+				return Err(syn::Error::new(attr.span(), msg))
+			}
+			message = Some(attr.parse_args::<syn::LitStr>()?);
+		}
+		let message = message.ok_or_else(|| {
+			let msg = "Invalid error, expected `#[error(\"..\")]` attribute";
+			syn::Error::new(variant.ident.span(), msg)
+		})?;
+
+		variants.push(&variant.ident);
+		messages.push(message);
+	}
+
+	Ok(quote! {
+		impl #impl_generics From<#error_enum #ty_generics> for &'static str #where_clause {
+			fn from(error: #error_enum #ty_generics) -> &'static str {
+				match error {
+					#( #error_enum::#variants => #messages, )*
+				}
+			}
+		}
+
+		impl #impl_generics crate::support::metadata::GetErrorMetadata for #error_enum #ty_generics #where_clause {
+			fn error_metadata() -> Vec<&'static str> {
+				vec![ #( #messages ),* ]
+			}
+		}
+	})
+}
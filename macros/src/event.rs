@@ -0,0 +1,57 @@
+use super::metadata;
+use quote::quote;
+use syn::spanned::Spanned;
+
+/// See the `fn event_metadata` docs at the `lib.rs` of this crate for a high level definition.
+pub fn event_metadata(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let item = syn::parse_macro_input!(item as syn::DeriveInput);
+	match expand_event_metadata(item) {
+		Ok(generated) => generated.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+fn expand_event_metadata(item: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+	let data = if let syn::Data::Enum(data) = &item.data {
+		data
+	} else {
+		return Err(syn::Error::new(item.span(), "Invalid event, expected an enum"))
+	};
+
+	let event_enum = &item.ident;
+	let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+	// One `EventMetadata` per variant, with the name and types of its fields. Unnamed fields are
+	// named after their position.
+	let events = data.variants.iter().map(|variant| {
+		let name = variant.ident.to_string();
+		let docs = metadata::docs(&variant.attrs);
+		let fields = variant.fields.iter().enumerate().map(|(i, field)| {
+			let name = field.ident.as_ref().map_or(i.to_string(), |ident| ident.to_string());
+			let ty = &field.ty;
+			let description = metadata::type_description(ty);
+			quote! {
+				crate::support::metadata::FieldMetadata {
+					name: #name,
+					ty: #description,
+					type_name: core::any::type_name::<#ty>(),
+				}
+			}
+		});
+		quote! {
+			crate::support::metadata::EventMetadata {
+				name: #name,
+				fields: vec![ #( #fields ),* ],
+				docs: vec![ #( #docs ),* ],
+			}
+		}
+	});
+
+	Ok(quote! {
+		impl #impl_generics crate::support::metadata::GetEventMetadata for #event_enum #ty_generics #where_clause {
+			fn event_metadata() -> Vec<crate::support::metadata::EventMetadata> {
+				vec![ #( #events ),* ]
+			}
+		}
+	})
+}
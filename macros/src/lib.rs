@@ -1,6 +1,62 @@
+//! The macros used to build the pallets and the runtime of the state machine.
+//!
+//! The code generated for the pallets refers to the `support` module of the state machine as
+//! `crate::support`, so every crate defining pallets with these macros, whether a binary or a
+//! library, must have that module (or a re-export of it) at its root. The runtime macro is given
+//! the path of `support` instead, so a runtime can also be built from pallets of other crates. The
+//! pallets and the runtime themselves can be defined in any module. `examples/library_runtime.rs`
+//! builds two runtimes in the same module of a library crate this way.
+
 mod call;
+mod error;
+mod event;
+mod metadata;
 mod runtime;
+mod storage;
 
+/// Expand the callable functions of a pallet.
+///
+/// This is placed on the `impl` block of a pallet, where every function is considered callable,
+/// unless it is marked with `#[skip_call]`. Each callable function must take `&mut self` followed
+/// by `caller: T::AccountId`. A function which needs access to other pallets can take
+/// `runtime: &mut T` instead of `self`, and reaches every pallet, including its own, through
+/// `support::HasPallet`. Helper functions marked with `#[skip_call]` can have any signature. The
+/// arguments which a function does not use can start with an underscore, like `_caller`, which is
+/// left out of their name in the `Call` enum, its metadata and `#[weight(..)]`.
+///
+/// A call can be given a stable index with `#[call_index(n)]`, which must be unique within the
+/// pallet. Like the discriminants of an enum, a call without one takes the index following the
+/// previous call, starting from 0. With `#[macros::call(explicit_call_index)]`, every call must
+/// be given an index.
+///
+/// The errors of the pallet are declared by an enum deriving `macros::Error`, given to the macro
+/// as `#[macros::call(error = Error)]`. They are listed in the metadata of the pallet, while the
+/// errors of other pallets which its calls pass on, like those of a `support::Currency`, are
+/// listed under those pallets.
+///
+/// A call can be annotated with `#[weight(expr)]`, where `expr` evaluates to the
+/// `support::Weight` of the call and can use references to its arguments. Calls without it weigh
+/// `support::DEFAULT_WEIGHT`.
+///
+/// The impl block can be generic over an instance `I` of the pallet, next to `T`, like
+/// `impl<T: Config<I>, I> Pallet<T, I>`, so that a runtime can include several instances of the
+/// pallet. The generated `Call` is generic over the same parameters, with `I` defaulting to `()`.
+///
+/// This generates:
+/// - `enum Call` - an enum with one variant per callable function, containing its arguments.
+///   It also has a hidden variant which can never be built, which uses the generic parameters.
+///   `Call::call_index()` returns the index of a call.
+/// - implements the trait `support::PalletDispatch` to route a `Call` to the matching function. The
+///   runtime must implement `support::HasPallet` for the pallet, which the calls taking `self` are
+///   made on.
+/// - implements the trait `support::GetWeight` for `Call`.
+/// - implements `Clone`, `Debug`, `PartialEq` and `Eq` for `Call`, when the types of all the
+///   arguments implement them, and `Display` when they implement `support::DisplayArg`. A call is
+///   displayed like `transfer(to: dayitva, amount: 30)`.
+/// - implements `FromStr` for `Call`, when the types of all the arguments implement
+///   `support::parse::ParseArg`, to parse calls written like `transfer(to="bob", amount=10)`.
+/// - implements the trait `support::metadata::GetCallMetadata` for `Call`, describing each function
+///   with its doc comments and arguments, and listing the declared errors of the pallet.
 #[proc_macro_attribute]
 pub fn call(
 	attr: proc_macro::TokenStream,
@@ -9,22 +65,111 @@ pub fn call(
 	call::call(attr, item)
 }
 
+/// Describe the variants of a pallet `Event` enum.
+///
+/// This implements the trait `support::metadata::GetEventMetadata`, with the name, fields and doc
+/// comments of each variant, so that the events appear in the metadata of the runtime.
+#[proc_macro_derive(EventMetadata)]
+pub fn event_metadata(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	event::event_metadata(item)
+}
+
+/// Declare the errors of a pallet.
+///
+/// This is placed on an enum whose variants have no fields, each with its message given by
+/// `#[error("..")]`. It implements `From<Error> for &'static str`, so that an error can be returned
+/// from a call with `?` or `.into()`, and the trait `support::metadata::GetErrorMetadata`, which
+/// lists the messages for the metadata of the runtime.
+#[proc_macro_derive(Error, attributes(error))]
+pub fn error(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	error::error(item)
+}
+
+/// Declare the storage of a pallet.
+///
+/// This is placed on the pallet struct, above its derives. Its fields of the types
+/// `support::storage::StorageValue`, `StorageMap` and `StorageDoubleMap` are storage items, which
+/// can be annotated with `#[storage(getter = name, setter = name, default = expr)]`:
+/// - `getter` generates a function with this name, which takes references to the keys of the item
+///   and returns the stored value, with the doc comments of the field.
+/// - `setter` generates a function with this name, which takes the keys of the item and the value
+///   to store under them.
+/// - `default` is the value returned for keys which are not stored, instead of
+///   `Default::default()`.
+///
+/// The storage types take an optional last type argument, the `support::storage::QueryKind` of
+/// the item. With `OptionQuery`, like `StorageMap<K, V, OptionQuery>`, the values are stored
+/// directly, reading a key which is not stored returns `None`, and the getter and setter take and
+/// return an `Option<V>`, where setting `None` removes the value. The item then has no default.
+///
+/// This generates `fn new()`, which creates every storage item empty, with its default value and a
+/// prefix made of the module of the pallet and the name of the item. The other fields of the struct
+/// are created with `Default::default()`. When the pallet struct has a second type parameter, like
+/// `Pallet<T, I>`, it is the instance of the pallet: it must implement `support::Instance`, whose
+/// `PREFIX` is inserted in the prefix of each item, so that each instance has its own storage.
+///
+/// The items stay in the pallet struct, but together they are also exposed through
+/// `support::storage::RawStorage`, which reads and writes encoded values under raw keys starting
+/// with the hash of the prefix of each item. This is implemented whenever all the items support it,
+/// and `#[macros::runtime]` combines the pallets into a single key/value store for the runtime.
+#[proc_macro_attribute]
+pub fn storage(
+	attr: proc_macro::TokenStream,
+	item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+	storage::storage(attr, item)
+}
+
 /// Expand the `Runtime` definition.
 ///
-/// This generates function implementations on `Runtime`:
+/// The macro takes the type of the blocks executed by the runtime, and optionally the paths of the
+/// module of the system pallet and of the `support` module, which default to `crate::system` and
+/// `crate::support`:
+/// `#[macros::runtime(block = types::Block, system = crate::system, support = crate::support)]`.
+/// The block type must be a `support::Block` of `support::Extrinsic`s. The runtime can be defined
+/// in any module of a binary or library crate. The type of each field is used to find its pallet,
+/// and the `Call` of each pallet is given by its `support::PalletDispatch` implementation.
+///
+/// This generates public function implementations on `Runtime`:
 /// - `fn new()` - which generates a new instance of the runtime, by instantiating all the pallets
 ///   included in the runtime.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
 ///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   valid block number. Once the extrinsics are executed, the `support::Hooks::on_finalize` hook
+///   of every pallet is called. The system pallet is not included. It returns a
+///   `support::BlockReceipt` with the result, events and weight of every extrinsic. When the
+///   `support::ExecutionMode` of the system pallet is `Strict`, the first failing extrinsic
+///   rejects the block: the block is executed in a storage transaction, which is rolled back, and
+///   its error is returned.
+/// - `fn dry_run()` - which executes a single extrinsic in a storage transaction, as if it was part
+///   of the next block, and returns a `support::DryRun` with its result, events and weight. The
+///   transaction is rolled back, so the state of the runtime is not modified.
+/// - `fn collect_events()` - which moves the events of every pallet, including the ones emitted by
+///   the system pallet itself, into the system pallet, after each dispatched call and at the end
+///   of the block.
 ///
-/// This also generates code needed for dispatching calls to the pallets:
-/// - Note: For simplicity, we assume that the system pallet is not callable.
+/// This also generates code needed for dispatching calls to the pallets, in enums named after the
+/// runtime, like `RuntimeCall` and `RuntimeEvent` for `Runtime`, or `ChainCall` and `ChainEvent`
+/// for `Chain`, so that many runtimes can be defined in the same module:
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
-///   all pallets. The system pallet is not included.
+///   all pallets, starting with the system pallet. It derives `Debug`, `Clone`, `PartialEq` and
+///   `Eq`, and is displayed like `balances.transfer(to: dayitva, amount: 30)`. It implements
+///   `FromStr` to parse calls written like `balances.transfer(to="bob", amount=10)`.
+/// - `enum RuntimeEvent` - an "outer"-enum representing the events of all pallets, including
+///   system, as given by `support::TakeEvents`. It derives `Debug`, `Clone` and `PartialEq`, so
+///   the `Runtime` struct must derive them as well.
+/// - `RuntimeCall::call_index()` - which returns the index of the pallet of a call in the runtime,
+///   where `system` is 0, and the index of the call within its pallet.
+/// - implements the trait `support::GetWeight` for `RuntimeCall`, using the weight of each pallet
+///   call.
+/// - `fn metadata()` - which describes every pallet of the runtime with its index, calls, events
+///   and errors, as a `support::metadata::RuntimeMetadata`.
 /// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
-///   logic like incrementing the nonce of the user is included in the generated code. The system
-///   pallet is not included.
+///   logic like incrementing the nonce of the user is included in the generated code. Since the
+///   pallets are handed the whole runtime, their calls can dispatch nested `RuntimeCall`s through
+///   the runtime on behalf of the same caller.
+/// - implements the trait `support::HasPallet` for every pallet, including system, so pallets can
+///   reach each other through the runtime they are dispatched with.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
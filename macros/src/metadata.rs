@@ -0,0 +1,117 @@
+use quote::ToTokens;
+
+/// Collect the lines of the doc comments found in `attrs`, without the single space which follows
+/// `///`, so that any further indentation is kept.
+pub fn docs(attrs: &[syn::Attribute]) -> Vec<String> {
+	attrs
+		.iter()
+		.filter(|attr| attr.path().is_ident("doc"))
+		.filter_map(|attr| match &attr.meta {
+			syn::Meta::NameValue(syn::MetaNameValue {
+				value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+				..
+			}) => {
+				let doc = doc.value();
+				Some(doc.strip_prefix(' ').unwrap_or(&doc).to_string())
+			},
+			_ => None,
+		})
+		.collect()
+}
+
+/// Describe a type as it is written in the source, such as `Vec<T::AccountId>`.
+pub fn type_description(ty: &syn::Type) -> String {
+	match ty {
+		syn::Type::Path(syn::TypePath { qself: None, path }) => path_description(path),
+		syn::Type::Path(syn::TypePath { qself: Some(qself), path }) => {
+			// In `<T as Config>::Balance`, the path is `Config::Balance` and the position tells
+			// how many of its segments belong to the trait.
+			let segments = path.segments.iter().map(segment_description).collect::<Vec<_>>();
+			let (as_trait, rest) = segments.split_at(qself.position);
+			let leading = if path.leading_colon.is_some() { "::" } else { "" };
+			let self_ty = type_description(&qself.ty);
+			if as_trait.is_empty() {
+				return format!("<{}>::{}", self_ty, rest.join("::"))
+			}
+			format!("<{} as {}{}>::{}", self_ty, leading, as_trait.join("::"), rest.join("::"))
+		},
+		syn::Type::Reference(reference) => {
+			let lifetime = reference.lifetime.as_ref().map(|lifetime| format!("{} ", lifetime));
+			let mutability = if reference.mutability.is_some() { "mut " } else { "" };
+			format!(
+				"&{}{}{}",
+				lifetime.unwrap_or_default(),
+				mutability,
+				type_description(&reference.elem)
+			)
+		},
+		syn::Type::Tuple(tuple) => {
+			let elems = tuple.elems.iter().map(type_description).collect::<Vec<_>>();
+			match elems.as_slice() {
+				[elem] => format!("({},)", elem),
+				elems => format!("({})", elems.join(", ")),
+			}
+		},
+		syn::Type::Array(array) => {
+			format!("[{}; {}]", type_description(&array.elem), expr_description(&array.len))
+		},
+		syn::Type::Slice(slice) => format!("[{}]", type_description(&slice.elem)),
+		syn::Type::Paren(paren) => format!("({})", type_description(&paren.elem)),
+		syn::Type::Group(group) => type_description(&group.elem),
+		syn::Type::Never(_) => "!".to_string(),
+		syn::Type::Infer(_) => "_".to_string(),
+		// The other kinds of types are not expected in calls and events, and are described by
+		// their tokens.
+		ty => ty.to_token_stream().to_string(),
+	}
+}
+
+/// Describe a path, such as `T::AccountId` or `std::vec::Vec<u8>`.
+fn path_description(path: &syn::Path) -> String {
+	let segments = path.segments.iter().map(segment_description).collect::<Vec<_>>();
+	let leading = if path.leading_colon.is_some() { "::" } else { "" };
+	format!("{}{}", leading, segments.join("::"))
+}
+
+/// Describe one segment of a path, with its generic arguments.
+fn segment_description(segment: &syn::PathSegment) -> String {
+	let ident = segment.ident.to_string();
+	match &segment.arguments {
+		syn::PathArguments::None => ident,
+		syn::PathArguments::AngleBracketed(arguments) => {
+			let arguments = arguments.args.iter().map(argument_description).collect::<Vec<_>>();
+			format!("{}<{}>", ident, arguments.join(", "))
+		},
+		syn::PathArguments::Parenthesized(arguments) => {
+			let inputs = arguments.inputs.iter().map(type_description).collect::<Vec<_>>();
+			match &arguments.output {
+				syn::ReturnType::Default => format!("{}({})", ident, inputs.join(", ")),
+				syn::ReturnType::Type(_, output) => {
+					format!("{}({}) -> {}", ident, inputs.join(", "), type_description(output))
+				},
+			}
+		},
+	}
+}
+
+/// Describe a generic argument of a path segment, such as the `u8` in `Vec<u8>`.
+fn argument_description(argument: &syn::GenericArgument) -> String {
+	match argument {
+		syn::GenericArgument::Lifetime(lifetime) => lifetime.to_string(),
+		syn::GenericArgument::Type(ty) => type_description(ty),
+		syn::GenericArgument::Const(expr) => expr_description(expr),
+		syn::GenericArgument::AssocType(assoc) => {
+			format!("{} = {}", assoc.ident, type_description(&assoc.ty))
+		},
+		argument => argument.to_token_stream().to_string(),
+	}
+}
+
+/// Describe an expression found in a type, such as the length of an array.
+fn expr_description(expr: &syn::Expr) -> String {
+	match expr {
+		syn::Expr::Lit(lit) => lit.to_token_stream().to_string(),
+		syn::Expr::Path(path) if path.qself.is_none() => path_description(&path.path),
+		expr => expr.to_token_stream().to_string(),
+	}
+}
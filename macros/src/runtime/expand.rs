@@ -3,43 +3,170 @@ use quote::quote;
 
 /// See the `fn runtime` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
-	let RuntimeDef { runtime_struct, pallets } = def;
+	let RuntimeDef { runtime_struct, block, system, support, pallets } = def;
+
+	// The outer enums are named after the runtime, like `RuntimeCall` for `Runtime`, so that many
+	// runtimes can be defined in the same module.
+	let runtime_call = quote::format_ident!("{}Call", runtime_struct);
+	let runtime_event = quote::format_ident!("{}Event", runtime_struct);
 
 	// This is a vector of all the pallet names, not including system.
 	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
 	// This is a vector of all the pallet types, not including system.
 	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
 
+	// The callable pallets come first with `system`, followed by the other pallets.
+	let call_names = std::iter::once(syn::parse_quote!(system))
+		.chain(pallet_names.iter().cloned())
+		.collect::<Vec<syn::Ident>>();
+	let call_types = std::iter::once(syn::parse_quote!(#system::Pallet<#runtime_struct>))
+		.chain(pallet_types.iter().cloned())
+		.collect::<Vec<syn::Type>>();
+
+	// The `Call` enum of each callable pallet, as given by its `support::PalletDispatch`
+	// implementation.
+	let pallet_calls = call_types
+		.iter()
+		.map(|type_| quote!(<#type_ as #support::PalletDispatch<#runtime_struct>>::Call))
+		.collect::<Vec<_>>();
+
+	// The index of each pallet in the `Runtime` struct, where `system` is 0.
+	let pallet_indices = (0..call_names.len() as u32).collect::<Vec<_>>();
+
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
 		impl #runtime_struct {
 			// Create a new instance of the main Runtime, by creating a new instance of each pallet.
-			fn new() -> Self {
+			pub fn new() -> Self {
 				Self {
 					// Since system is not included in the list of pallets, we manually add it here.
-					system: <system::Pallet::<Self>>::new(),
+					system: <#system::Pallet<Self>>::new(),
 					#(
 						#pallet_names: <#pallet_types>::new()
 					),*
 				}
 			}
 
-			// Execute a block of extrinsics. Increments the block number.
-			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
-				self.system.inc_block_number();
-				if block.header.block_number != self.system.block_number() {
+			// Execute a block of extrinsics, and then the `on_finalize` hook of every pallet.
+			// Increments the block number, and returns the outcome of every extrinsic.
+			pub fn execute_block(
+				&mut self,
+				block: #block,
+			) -> Result<
+				#support::BlockReceipt<
+					<#runtime_struct as #system::Config>::BlockNumber,
+					<#runtime_struct as #system::Config>::AccountId,
+					#runtime_event,
+				>,
+				&'static str,
+			> {
+				// The header is checked before any state is changed, so a rejected block leaves
+				// no trace.
+				if Some(block.header.block_number) != self.system.next_block_number() {
 					return Err(&"block number does not match what is expected")
 				}
-				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
+				// In strict mode, the whole block is rejected if any of its extrinsics fails, so it
+				// is executed in a storage transaction, which is rolled back on the first failure.
+				// The events of the previous block are cleared outside of the transaction, and put
+				// back if it is rolled back.
+				let strict = match self.system.execution_mode() {
+					#support::ExecutionMode::Strict => true,
+					#support::ExecutionMode::Lenient => false,
+				};
+				let previous_events = self.system.reset_events();
+				if strict {
+					#support::storage::Transactional::start_transaction(self);
+				}
+				self.system.inc_block_number();
+				self.system.set_now(block.header.timestamp);
+				let mut extrinsics = Vec::new();
+				for (i, #support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
 					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
-						eprintln!(
-							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
-							block.header.block_number, i, e
-						)
+					let weight = #support::GetWeight::weight(&call);
+					let first_event = self.system.events().len();
+					let result = #support::Dispatch::dispatch(self, caller.clone(), call);
+					if let Err(e) = result {
+						if strict {
+							#support::storage::Transactional::rollback_transaction(self);
+							self.system.restore_events(previous_events);
+							return Err(e);
+						}
+					}
+					extrinsics.push(#support::ExtrinsicReceipt {
+						index: i as u32,
+						caller,
+						result,
+						events: self.system.events()[first_event..].to_vec(),
+						weight,
 					});
 				}
-				Ok(())
+				let first_event = self.system.events().len();
+				// Give each pallet the chance to run its end of block logic.
+				#(
+					<#pallet_types as #support::Hooks<Self>>::on_finalize(self);
+				)*
+				self.collect_events();
+				if strict {
+					#support::storage::Transactional::commit_transaction(self);
+				}
+				Ok(#support::BlockReceipt {
+					block_number: block.header.block_number,
+					extrinsics,
+					finalize_events: self.system.events()[first_event..].to_vec(),
+				})
+			}
+
+			// Execute an extrinsic in a storage transaction, as if it was the only extrinsic of
+			// the next block, and return its outcome. The transaction is always rolled back, so
+			// the state of the runtime is left untouched. The next block has no header, so the
+			// time stays the one of the current block.
+			pub fn dry_run(
+				&mut self,
+				extrinsic: #support::Extrinsic<
+					<#runtime_struct as #system::Config>::AccountId,
+					#runtime_call,
+				>,
+			) -> #support::DryRun<#runtime_event> {
+				#support::storage::Transactional::start_transaction(self);
+				self.system.inc_block_number();
+				let first_event = self.system.events().len();
+
+				let #support::Extrinsic { caller, call } = extrinsic;
+				self.system.inc_nonce(&caller);
+				let weight = #support::GetWeight::weight(&call);
+				let result = #support::Dispatch::dispatch(self, caller, call);
+				let events = self.system.events()[first_event..].to_vec();
+				#support::storage::Transactional::rollback_transaction(self);
+				#support::DryRun { result, events, weight }
+			}
+
+			// Describe every pallet of the runtime, with its calls, events and errors.
+			pub fn metadata() -> #support::metadata::RuntimeMetadata {
+				use #support::metadata::{GetCallMetadata, GetEventMetadata, PalletMetadata};
+
+				#support::metadata::RuntimeMetadata {
+					pallets: vec![
+						#(
+							PalletMetadata {
+								name: stringify!(#call_names),
+								index: #pallet_indices,
+								calls: <#pallet_calls>::call_metadata(),
+								events: <<#call_types as #support::TakeEvents>::Event>::event_metadata(),
+								errors: <#pallet_calls>::error_metadata(),
+							}
+						),*
+					],
+				}
+			}
+
+			// Move the events emitted by every pallet, including system itself, into the system
+			// pallet, wrapped in a `RuntimeEvent`.
+			fn collect_events(&mut self) {
+				#(
+					for event in #support::TakeEvents::take_events(&mut self.#call_names) {
+						self.system.deposit_event(#runtime_event::#call_names(event));
+					}
+				)*
 			}
 		}
 	};
@@ -51,13 +178,82 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
-		pub enum RuntimeCall {
-			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
+		#[derive(Debug, Clone, PartialEq, Eq)]
+		pub enum #runtime_call {
+			#( #call_names(#pallet_calls) ),*
+		}
+
+		// These are all the events which can be emitted by the pallets, collected in the system
+		// pallet.
+		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone, PartialEq)]
+		pub enum #runtime_event {
+			#( #call_names(<#call_types as #support::TakeEvents>::Event) ),*
+		}
+
+		impl #runtime_call {
+			// The index of the pallet in the runtime, and the index of the call in the pallet.
+			pub fn call_index(&self) -> (u32, u32) {
+				match self {
+					#(
+						#runtime_call::#call_names(call) => (#pallet_indices, call.call_index()),
+					)*
+				}
+			}
+		}
+
+		// Display the call like `balances.transfer(to: dayitva, amount: 30)`.
+		impl core::fmt::Display for #runtime_call {
+			fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+				match self {
+					#(
+						#runtime_call::#call_names(call) => write!(f, "{}.{}", stringify!(#call_names), call),
+					)*
+				}
+			}
+		}
+
+		// Calls are arguments of other calls when they are batched.
+		impl #support::DisplayArg for #runtime_call {
+			fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+				core::fmt::Display::fmt(self, f)
+			}
+		}
+
+		// Parse a call written like `balances.transfer(to="bob", amount=10)`.
+		impl core::str::FromStr for #runtime_call {
+			type Err = String;
+
+			fn from_str(s: &str) -> Result<Self, String> {
+				let (pallet, call) = #support::parse::split_pallet(s)?;
+				match pallet {
+					#(
+						stringify!(#call_names) => call.parse().map(#runtime_call::#call_names),
+					)*
+					_ => Err(format!("unknown pallet `{}`", pallet)),
+				}
+			}
+		}
+
+		impl #support::parse::ParseArg for #runtime_call {
+			fn parse_arg(s: &str) -> Result<Self, String> {
+				s.parse()
+			}
+		}
+
+		impl #support::GetWeight for #runtime_call {
+			fn weight(&self) -> #support::Weight {
+				match self {
+					#(
+						#runtime_call::#call_names(call) => #support::GetWeight::weight(call),
+					)*
+				}
+			}
 		}
 
-		impl crate::support::Dispatch for #runtime_struct {
-			type Caller = <Runtime as system::Config>::AccountId;
-			type Call = RuntimeCall;
+		impl #support::Dispatch for #runtime_struct {
+			type Caller = <#runtime_struct as #system::Config>::AccountId;
+			type Call = #runtime_call;
 			// Dispatch a call on behalf of a caller. Increments the caller's nonce.
 			//
 			// Dispatch allows us to identify which underlying pallet call we want to execute.
@@ -67,13 +263,21 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				&mut self,
 				caller: Self::Caller,
 				runtime_call: Self::Call,
-			) -> crate::support::DispatchResult {
+			) -> #support::DispatchResult {
 				// This match statement will allow us to correctly route `RuntimeCall`s
 				// to the appropriate pallet level call.
 				match runtime_call {
 					#(
-						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+						#runtime_call::#call_names(call) => {
+							// The pallet is handed the whole runtime, so that its calls can reach
+							// the other pallets, and dispatch nested `RuntimeCall`s.
+							let result = <#call_types as #support::PalletDispatch<Self>>::dispatch(
+								self,
+								caller,
+								call,
+							);
+							self.collect_events();
+							result?;
 						}
 					),*
 				}
@@ -82,10 +286,104 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		}
 	};
 
+	// This quote block gives pallets access to each other, including the system pallet.
+	let has_pallet_impl = quote! {
+		impl #support::HasPallet<#system::Pallet<#runtime_struct>> for #runtime_struct {
+			fn pallet(&self) -> &#system::Pallet<#runtime_struct> {
+				&self.system
+			}
+
+			fn pallet_mut(&mut self) -> &mut #system::Pallet<#runtime_struct> {
+				&mut self.system
+			}
+		}
+
+		#(
+			impl #support::HasPallet<#pallet_types> for #runtime_struct {
+				fn pallet(&self) -> &#pallet_types {
+					&self.#pallet_names
+				}
+
+				fn pallet_mut(&mut self) -> &mut #pallet_types {
+					&mut self.#pallet_names
+				}
+			}
+		)*
+	};
+
+	// This quote block exposes the storage of all the pallets, including system, as a single
+	// key/value store. Every key starts with the prefix of a storage item, so each key is routed to
+	// the pallet which owns it.
+	let raw_storage_impl = quote! {
+		impl #support::storage::RawStorage for #runtime_struct {
+			fn owns_key(&self, key: &[u8]) -> bool {
+				false #( || #support::storage::RawStorage::owns_key(&self.#call_names, key) )*
+			}
+
+			fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>> {
+				#(
+					if #support::storage::RawStorage::owns_key(&self.#call_names, key) {
+						return #support::storage::RawStorage::get_raw(&self.#call_names, key)
+					}
+				)*
+				None
+			}
+
+			fn set_raw(
+				&mut self,
+				key: &[u8],
+				value: &[u8],
+			) -> Result<(), #support::storage::RawStorageError> {
+				#(
+					if #support::storage::RawStorage::owns_key(&self.#call_names, key) {
+						return #support::storage::RawStorage::set_raw(&mut self.#call_names, key, value)
+					}
+				)*
+				Err(#support::storage::RawStorageError::UnknownKey)
+			}
+
+			fn kill_raw(&mut self, key: &[u8]) -> Result<(), #support::storage::RawStorageError> {
+				#(
+					if #support::storage::RawStorage::owns_key(&self.#call_names, key) {
+						return #support::storage::RawStorage::kill_raw(&mut self.#call_names, key)
+					}
+				)*
+				Err(#support::storage::RawStorageError::UnknownKey)
+			}
+		}
+	};
+
+	// This quote block opens and closes transactions on the storage of all the pallets, including
+	// system, and on the events collected by system. The events still pending in the pallets are
+	// collected first, so that they are kept or discarded with the transaction they belong to.
+	let transactional_impl = quote! {
+		impl #support::storage::Transactional for #runtime_struct {
+			fn start_transaction(&mut self) {
+				self.collect_events();
+				self.system.start_events_transaction();
+				#( #support::storage::Transactional::start_transaction(&mut self.#call_names); )*
+			}
+
+			fn commit_transaction(&mut self) {
+				self.collect_events();
+				self.system.commit_events_transaction();
+				#( #support::storage::Transactional::commit_transaction(&mut self.#call_names); )*
+			}
+
+			fn rollback_transaction(&mut self) {
+				self.collect_events();
+				self.system.rollback_events_transaction();
+				#( #support::storage::Transactional::rollback_transaction(&mut self.#call_names); )*
+			}
+		}
+	};
+
 	// We combine and return all the generated code.
 	quote! {
 		#dispatch_impl
 		#runtime_impl
+		#has_pallet_impl
+		#raw_storage_impl
+		#transactional_impl
 	}
-	.into()
 }
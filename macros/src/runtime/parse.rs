@@ -1,17 +1,67 @@
 use syn::spanned::Spanned;
 
+/// The arguments given to the macro, as in `#[macros::runtime(block = types::Block)]`.
+#[derive(Debug)]
+pub struct RuntimeArgs {
+	/// The type of the blocks executed by the runtime, given with `block = ..`.
+	pub block: syn::Type,
+	/// The path of the module of the system pallet, given with `system = ..`. It defaults to
+	/// `crate::system`.
+	pub system: syn::Path,
+	/// The path of the `support` module, given with `support = ..`. It defaults to
+	/// `crate::support`.
+	pub support: syn::Path,
+}
+
+impl syn::parse::Parse for RuntimeArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut block = None;
+		let mut system = None;
+		let mut support = None;
+		let parser = syn::meta::parser(|meta| {
+			if meta.path.is_ident("block") {
+				block = Some(meta.value()?.parse::<syn::Type>()?);
+				Ok(())
+			} else if meta.path.is_ident("system") {
+				system = Some(meta.value()?.parse::<syn::Path>()?);
+				Ok(())
+			} else if meta.path.is_ident("support") {
+				support = Some(meta.value()?.parse::<syn::Path>()?);
+				Ok(())
+			} else {
+				Err(meta.error("Invalid runtime, expected `block`, `system` or `support`"))
+			}
+		});
+		syn::parse::Parser::parse2(parser, input.parse()?)?;
+
+		let block = block.ok_or_else(|| {
+			let msg = "Invalid runtime, expected the type of the blocks, like `block = types::Block`";
+			syn::Error::new(proc_macro2::Span::call_site(), msg)
+		})?;
+		let system = system.unwrap_or_else(|| syn::parse_quote!(crate::system));
+		let support = support.unwrap_or_else(|| syn::parse_quote!(crate::support));
+		Ok(Self { block, system, support })
+	}
+}
+
 /// This object will collect all the information we need to keep while parsing the `Runtime` struct.
 #[derive(Debug)]
 pub struct RuntimeDef {
 	/// This is the name of the struct used by the user. We mostly assume it is `Runtime`.
 	pub runtime_struct: syn::Ident,
+	/// The type of the blocks executed by the runtime.
+	pub block: syn::Type,
+	/// The path of the module of the system pallet.
+	pub system: syn::Path,
+	/// The path of the `support` module.
+	pub support: syn::Path,
 	/// This is the list of pallets included in the `Runtime` struct. We omit `system` from this
 	/// list, but during parsing we check that system exists.
 	pub pallets: Vec<(syn::Ident, syn::Type)>,
 }
 
 impl RuntimeDef {
-	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+	pub fn try_from(args: RuntimeArgs, item: syn::Item) -> syn::Result<Self> {
 		// First we check that we are parsing a `struct`.
 		let item_struct = if let syn::Item::Struct(item) = item {
 			item
@@ -33,34 +83,36 @@ impl RuntimeDef {
 			}
 		}
 
-		Ok(Self { runtime_struct, pallets })
+		let RuntimeArgs { block, system, support } = args;
+		Ok(Self { runtime_struct, block, system, support, pallets })
 	}
 }
 
 /// This function checks that the `system` pallet is the first pallet included in the `Runtime`
 /// struct. We make many assumptions about the `system` pallet in order to keep these macros simple.
-/// For example, we assume that the system pallet has no callable functions, and that it contains
-/// specific functions like incrementing the block number and a user's nonce.
+/// For example, we assume that the system pallet contains specific functions like incrementing the
+/// block number and a user's nonce, and that it collects the events of every pallet.
 ///
 /// You can consider these macros to be tightly coupled to the logic of the `system` pallet.
 fn check_system(item_struct: &syn::ItemStruct) -> syn::Result<()> {
 	// Extract the name of the first field in the `Runtime` struct.
-	let first_field_name = if let Some(first_field) = item_struct.fields.iter().next() {
-		if let Some(field_name) = &first_field.ident {
-			field_name.to_string()
-		} else {
-			let msg = "first field is expected to have the name system";
-			return Err(syn::Error::new(item_struct.span(), msg))
-		}
+	let first_field = if let Some(first_field) = item_struct.fields.iter().next() {
+		first_field
 	} else {
 		let msg = "runtime struct is expected to have fields";
-		return Err(syn::Error::new(item_struct.span(), msg))
+		return Err(syn::Error::new(item_struct.ident.span(), msg))
+	};
+	let first_field_name = if let Some(field_name) = &first_field.ident {
+		field_name
+	} else {
+		let msg = "first field is expected to have the name system";
+		return Err(syn::Error::new(first_field.span(), msg))
 	};
 
 	// Check if the first field is named "system"
 	if first_field_name != "system" {
 		let msg = "first field is expected to be named system";
-		return Err(syn::Error::new(item_struct.span(), msg))
+		return Err(syn::Error::new(first_field_name.span(), msg))
 	}
 
 	Ok(())
@@ -0,0 +1,195 @@
+use super::parse::{StorageDef, StorageItemDef, StorageKind};
+use quote::quote;
+
+/// See the `fn storage` docs at the `lib.rs` of this crate for a high level definition.
+pub fn expand_storage(def: StorageDef) -> proc_macro2::TokenStream {
+	let StorageDef { pallet_struct, generics, instance, items, other_fields } = def;
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	// The prefix of the items of an instance of the pallet also holds the instance, so that the
+	// items of each instance are distinct in the runtime.
+	let mut new_generics = generics.clone();
+	let instance_prefix = match &instance {
+		Some(instance) => {
+			new_generics
+				.make_where_clause()
+				.predicates
+				.push(syn::parse_quote!(#instance: crate::support::Instance));
+			quote!(<#instance as crate::support::Instance>::PREFIX)
+		},
+		None => quote!(""),
+	};
+	let (_, _, new_where_clause) = new_generics.split_for_impl();
+
+	// Each storage item is created empty through the type of its field, with a prefix made of the
+	// module of the pallet, its instance and the name of the item, and its default value.
+	let item_init = items.iter().map(|item| {
+		let name = &item.name;
+		let ty = &item.ty;
+		let default = match &item.default {
+			Some(default) => quote!(#default),
+			None => quote!(Default::default()),
+		};
+		quote! {
+			#name: <#ty>::new(
+				format!("{}::{}{}", module_path!(), #instance_prefix, stringify!(#name)),
+				#default,
+			)
+		}
+	});
+
+	// The type which reading each item returns, as given by its query kind, which is the type of
+	// its values unless the query kind is given.
+	let query_type = |item: &StorageItemDef| {
+		let value = &item.value;
+		match &item.query {
+			Some(query) => quote!(<#query as crate::support::storage::QueryKind>::Query<#value>),
+			None => quote!(#value),
+		}
+	};
+
+	// The getters return the value stored under the given keys, or what the query kind of the item
+	// gives when nothing is stored.
+	let getters = items.iter().filter_map(|item| {
+		let getter = item.getter.as_ref()?;
+		let name = &item.name;
+		let docs = &item.docs;
+		let value = query_type(item);
+		let key_name = match item.kind {
+			StorageKind::Value => vec![],
+			StorageKind::Map => vec![quote!(key)],
+			StorageKind::DoubleMap => vec![quote!(key1), quote!(key2)],
+		};
+		let key_type = &item.keys;
+		Some(quote! {
+			#( #docs )*
+			pub fn #getter(&self, #( #key_name: &#key_type ),*) -> #value {
+				self.#name.get(#( #key_name ),*)
+			}
+		})
+	});
+
+	// The setters store the value under the given keys, taking it as the getters return it.
+	let setters = items.iter().filter_map(|item| {
+		let setter = item.setter.as_ref()?;
+		let name = &item.name;
+		let value = query_type(item);
+		let key_name = match item.kind {
+			StorageKind::Value => vec![],
+			StorageKind::Map => vec![quote!(key)],
+			StorageKind::DoubleMap => vec![quote!(key1), quote!(key2)],
+		};
+		let key_type = &item.keys;
+		Some(quote! {
+			#[doc = concat!("Set the value stored in `", stringify!(#name), "`.")]
+			pub fn #setter(&mut self, #( #key_name: #key_type, )* value: #value) {
+				self.#name.set(#( #key_name, )* value)
+			}
+		})
+	});
+
+	// The pallet gives raw access to its storage by routing each key to the item which owns it.
+	// This is only implemented when all the items support it.
+	let item_names = items.iter().map(|item| &item.name).collect::<Vec<_>>();
+	let mut raw_generics = generics.clone();
+	let raw_where_clause = raw_generics.make_where_clause();
+	for item in &items {
+		let ty = &item.ty;
+		raw_where_clause.predicates.push(syn::parse_quote!(#ty: crate::support::storage::RawStorage));
+	}
+	let (_, _, raw_where_clause) = raw_generics.split_for_impl();
+	let raw_storage_impl = quote! {
+		impl #impl_generics crate::support::storage::RawStorage for #pallet_struct #ty_generics
+			#raw_where_clause
+		{
+			fn owns_key(&self, key: &[u8]) -> bool {
+				let _ = key;
+				false #( || crate::support::storage::RawStorage::owns_key(&self.#item_names, key) )*
+			}
+
+			fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>> {
+				let _ = key;
+				#(
+					if crate::support::storage::RawStorage::owns_key(&self.#item_names, key) {
+						return crate::support::storage::RawStorage::get_raw(&self.#item_names, key)
+					}
+				)*
+				None
+			}
+
+			fn set_raw(
+				&mut self,
+				key: &[u8],
+				value: &[u8],
+			) -> Result<(), crate::support::storage::RawStorageError> {
+				let _ = (key, value);
+				#(
+					if crate::support::storage::RawStorage::owns_key(&self.#item_names, key) {
+						return crate::support::storage::RawStorage::set_raw(&mut self.#item_names, key, value)
+					}
+				)*
+				Err(crate::support::storage::RawStorageError::UnknownKey)
+			}
+
+			fn kill_raw(&mut self, key: &[u8]) -> Result<(), crate::support::storage::RawStorageError> {
+				let _ = key;
+				#(
+					if crate::support::storage::RawStorage::owns_key(&self.#item_names, key) {
+						return crate::support::storage::RawStorage::kill_raw(&mut self.#item_names, key)
+					}
+				)*
+				Err(crate::support::storage::RawStorageError::UnknownKey)
+			}
+		}
+	};
+
+	// The pallet opens and closes transactions on all its items together.
+	let mut transactional_generics = generics.clone();
+	let transactional_where_clause = transactional_generics.make_where_clause();
+	for item in &items {
+		let ty = &item.ty;
+		transactional_where_clause
+			.predicates
+			.push(syn::parse_quote!(#ty: crate::support::storage::Transactional));
+	}
+	let (_, _, transactional_where_clause) = transactional_generics.split_for_impl();
+	let transactional_impl = quote! {
+		impl #impl_generics crate::support::storage::Transactional for #pallet_struct #ty_generics
+			#transactional_where_clause
+		{
+			fn start_transaction(&mut self) {
+				#( crate::support::storage::Transactional::start_transaction(&mut self.#item_names); )*
+			}
+
+			fn commit_transaction(&mut self) {
+				#( crate::support::storage::Transactional::commit_transaction(&mut self.#item_names); )*
+			}
+
+			fn rollback_transaction(&mut self) {
+				#( crate::support::storage::Transactional::rollback_transaction(&mut self.#item_names); )*
+			}
+		}
+	};
+
+	quote! {
+		#raw_storage_impl
+
+		#transactional_impl
+
+		impl #impl_generics #pallet_struct #ty_generics #new_where_clause {
+			/// Create a new instance of the pallet, with empty storage.
+			pub fn new() -> Self {
+				Self {
+					#( #item_init, )*
+					#( #other_fields: Default::default(), )*
+				}
+			}
+		}
+
+		impl #impl_generics #pallet_struct #ty_generics #where_clause {
+			#( #getters )*
+
+			#( #setters )*
+		}
+	}
+}
@@ -0,0 +1,28 @@
+pub mod expand;
+pub mod parse;
+
+/// See the `fn storage` docs at the `lib.rs` of this crate for a high level definition.
+pub fn storage(
+	_attr: proc_macro::TokenStream,
+	item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+	let mut item = syn::parse_macro_input!(item as syn::Item);
+
+	// First we parse the storage items of the pallet, which also removes the `#[storage]`
+	// attributes from its fields...
+	let generated = match parse::StorageDef::try_from(&mut item) {
+		// ..then we generate our new code.
+		Ok(def) => expand::expand_storage(def),
+		Err(e) => {
+			parse::remove_storage_attrs(&mut item);
+			e.to_compile_error()
+		},
+	};
+
+	// Return the pallet struct, followed by our generated code.
+	quote::quote! {
+		#item
+		#generated
+	}
+	.into()
+}
@@ -0,0 +1,193 @@
+use syn::spanned::Spanned;
+
+/// This object will collect all the information we need to keep while parsing the storage of a
+/// pallet.
+#[derive(Debug)]
+pub struct StorageDef {
+	/// This is the name of the pallet struct. We mostly assume it is `Pallet`.
+	pub pallet_struct: syn::Ident,
+	/// The generics and where clause of the pallet struct.
+	pub generics: syn::Generics,
+	/// The type parameter of the instance of the pallet, if it has one. This is its second type
+	/// parameter, following the `T` of its `Config`.
+	pub instance: Option<syn::Ident>,
+	/// The fields of the struct which are storage items. See `StorageItemDef`.
+	pub items: Vec<StorageItemDef>,
+	/// The other fields of the struct, which are not storage items.
+	pub other_fields: Vec<syn::Ident>,
+}
+
+/// The kinds of storage items supported by the macro, named after their types in
+/// `support::storage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+	Value,
+	Map,
+	DoubleMap,
+}
+
+/// This is the metadata we keep about each storage item of the pallet.
+#[derive(Debug)]
+pub struct StorageItemDef {
+	/// The name of the field.
+	pub name: syn::Ident,
+	/// The type of the field, which is used to create the item.
+	pub ty: syn::Type,
+	/// The doc comments of the field, which are copied onto its getter.
+	pub docs: Vec<syn::Attribute>,
+	/// The kind of storage item.
+	pub kind: StorageKind,
+	/// The types of the keys of the item, if it is a map.
+	pub keys: Vec<syn::Type>,
+	/// The type of the values stored in the item.
+	pub value: syn::Type,
+	/// The `support::storage::QueryKind` given as the last type argument of the item, if any.
+	pub query: Option<syn::Type>,
+	/// The name of the getter given with `#[storage(getter = ..)]`, if any.
+	pub getter: Option<syn::Ident>,
+	/// The name of the setter given with `#[storage(setter = ..)]`, if any.
+	pub setter: Option<syn::Ident>,
+	/// The default value given with `#[storage(default = ..)]`, if any.
+	pub default: Option<syn::Expr>,
+}
+
+impl StorageDef {
+	pub fn try_from(item: &mut syn::Item) -> syn::Result<Self> {
+		// First we check that we are parsing a `struct` with named fields.
+		let item_struct = if let syn::Item::Struct(item) = item {
+			item
+		} else {
+			return Err(syn::Error::new(item.span(), "Invalid pallet::storage, expected item struct"))
+		};
+		let fields = if let syn::Fields::Named(fields) = &mut item_struct.fields {
+			fields
+		} else {
+			let msg = "Invalid pallet::storage, expected a struct with named fields";
+			return Err(syn::Error::new(item_struct.span(), msg))
+		};
+
+		let mut items = vec![];
+		let mut other_fields = vec![];
+		for field in fields.named.iter_mut() {
+			let name = field.ident.clone().expect("fields are named");
+			let StorageAttr { getter, setter, default } = take_storage_attr(&mut field.attrs)?;
+
+			let Some((kind, mut types, query)) = storage_type(&field.ty)? else {
+				if getter.is_some() || setter.is_some() || default.is_some() {
+					let msg = "Invalid pallet::storage, `#[storage]` is only supported on \
+						`StorageValue`, `StorageMap` and `StorageDoubleMap` fields";
+					return Err(syn::Error::new(field.ty.span(), msg))
+				}
+				other_fields.push(name);
+				continue
+			};
+
+			let value = types.pop().expect("storage types have a value type");
+			let docs = field.attrs.iter().filter(|attr| attr.path().is_ident("doc")).cloned().collect();
+			let ty = field.ty.clone();
+			items.push(StorageItemDef {
+				name,
+				ty,
+				docs,
+				kind,
+				keys: types,
+				value,
+				query,
+				getter,
+				setter,
+				default,
+			});
+		}
+
+		Ok(Self {
+			pallet_struct: item_struct.ident.clone(),
+			generics: item_struct.generics.clone(),
+			instance: item_struct.generics.type_params().nth(1).map(|param| param.ident.clone()),
+			items,
+			other_fields,
+		})
+	}
+}
+
+/// The kind of a storage type, with the types of its keys and value, followed by its query kind, if
+/// it is given.
+type StorageType = (StorageKind, Vec<syn::Type>, Option<syn::Type>);
+
+/// Check if `ty` is one of the storage types, and return what it is made of.
+fn storage_type(ty: &syn::Type) -> syn::Result<Option<StorageType>> {
+	let segment = match ty {
+		syn::Type::Path(tp) if tp.qself.is_none() => tp.path.segments.last(),
+		_ => None,
+	};
+	let Some(segment) = segment else { return Ok(None) };
+
+	let (kind, expected) = match segment.ident.to_string().as_str() {
+		"StorageValue" => (StorageKind::Value, 1),
+		"StorageMap" => (StorageKind::Map, 2),
+		"StorageDoubleMap" => (StorageKind::DoubleMap, 3),
+		_ => return Ok(None),
+	};
+
+	let mut types = match &segment.arguments {
+		syn::PathArguments::AngleBracketed(args) => args
+			.args
+			.iter()
+			.filter_map(|arg| match arg {
+				syn::GenericArgument::Type(ty) => Some(ty.clone()),
+				_ => None,
+			})
+			.collect::<Vec<_>>(),
+		_ => vec![],
+	};
+	// The query kind is an optional last type argument.
+	let query = if types.len() == expected + 1 { types.pop() } else { None };
+	if types.len() != expected {
+		let msg = format!(
+			"Invalid pallet::storage, expected {} type arguments, and optionally a query kind",
+			expected
+		);
+		return Err(syn::Error::new(segment.span(), msg))
+	}
+	Ok(Some((kind, types, query)))
+}
+
+/// The arguments of the `#[storage(getter = .., setter = .., default = ..)]` attribute of a field.
+#[derive(Default)]
+struct StorageAttr {
+	getter: Option<syn::Ident>,
+	setter: Option<syn::Ident>,
+	default: Option<syn::Expr>,
+}
+
+/// Remove the `#[storage]` attribute from the attributes of a field, and return its arguments.
+fn take_storage_attr(attrs: &mut Vec<syn::Attribute>) -> syn::Result<StorageAttr> {
+	let mut storage_attr = StorageAttr::default();
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("storage")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("getter") {
+				storage_attr.getter = Some(meta.value()?.parse::<syn::Ident>()?);
+				Ok(())
+			} else if meta.path.is_ident("setter") {
+				storage_attr.setter = Some(meta.value()?.parse::<syn::Ident>()?);
+				Ok(())
+			} else if meta.path.is_ident("default") {
+				storage_attr.default = Some(meta.value()?.parse::<syn::Expr>()?);
+				Ok(())
+			} else {
+				Err(meta.error("Invalid pallet::storage, expected `getter`, `setter` or `default`"))
+			}
+		})?;
+	}
+	attrs.retain(|attr| !attr.path().is_ident("storage"));
+	Ok(storage_attr)
+}
+
+/// Remove all the `#[storage]` attributes from the fields of the struct, so that the struct can
+/// still be emitted when parsing it failed.
+pub fn remove_storage_attrs(item: &mut syn::Item) {
+	if let syn::Item::Struct(item_struct) = item {
+		for field in item_struct.fields.iter_mut() {
+			field.attrs.retain(|attr| !attr.path().is_ident("storage"));
+		}
+	}
+}
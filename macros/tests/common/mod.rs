@@ -0,0 +1,9 @@
+// The `support` and `system` modules of the state machine, which the code generated by the macros
+// relies on. The tests import them at their root, where the macros expect to find them.
+#![allow(dead_code)]
+
+#[path = "../../../src/support/mod.rs"]
+pub mod support;
+
+#[path = "../../../src/system.rs"]
+pub mod system;
@@ -0,0 +1,8 @@
+// The expected errors of the `fail` tests are kept in `.stderr` files next to them. After changing
+// an error message or its span on purpose, run the tests with `TRYBUILD=overwrite` to update them.
+#[test]
+fn ui() {
+	let t = trybuild::TestCases::new();
+	t.pass("tests/ui/pass/*.rs");
+	t.compile_fail("tests/ui/fail/*.rs");
+}
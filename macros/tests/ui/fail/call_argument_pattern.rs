@@ -0,0 +1,21 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	pub fn transfer(
+		&mut self,
+		caller: T::AccountId,
+		(to, amount): (T::AccountId, u32),
+	) -> Result<(), &'static str> {
+		let _ = (caller, to, amount);
+		Ok(())
+	}
+}
+
+fn main() {}
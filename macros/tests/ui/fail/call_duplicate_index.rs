@@ -0,0 +1,24 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	#[call_index(0)]
+	pub fn transfer(&mut self, caller: T::AccountId) -> Result<(), &'static str> {
+		let _ = caller;
+		Ok(())
+	}
+
+	#[call_index(0)]
+	pub fn burn(&mut self, caller: T::AccountId) -> Result<(), &'static str> {
+		let _ = caller;
+		Ok(())
+	}
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	pub fn transfer(&mut self) -> Result<(), &'static str> {
+		Ok(())
+	}
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call(explicit_call_index)]
+impl<T: Config> Pallet<T> {
+	#[call_index(0)]
+	pub fn transfer(&mut self, caller: T::AccountId) -> Result<(), &'static str> {
+		let _ = caller;
+		Ok(())
+	}
+
+	pub fn burn(&mut self, caller: T::AccountId) -> Result<(), &'static str> {
+		let _ = caller;
+		Ok(())
+	}
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	pub fn transfer(caller: T::AccountId) -> Result<(), &'static str> {
+		let _ = caller;
+		Ok(())
+	}
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call]
+pub fn transfer() {}
+
+fn main() {}
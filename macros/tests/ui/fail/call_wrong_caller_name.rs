@@ -0,0 +1,17 @@
+pub trait Config {
+	type AccountId;
+}
+
+pub struct Pallet<T: Config> {
+	_config: core::marker::PhantomData<T>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	pub fn transfer(&mut self, who: T::AccountId, amount: u32) -> Result<(), &'static str> {
+		let _ = (who, amount);
+		Ok(())
+	}
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[derive(Debug, macros::Error)]
+pub enum Error {
+	#[error("Overflow")]
+	Overflow,
+	Underflow,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[macros::runtime(system = crate::system)]
+pub struct Runtime {
+	system: u32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[macros::runtime(block = Block)]
+pub struct Runtime {
+	balances: u32,
+	system: u32,
+}
+
+fn main() {}
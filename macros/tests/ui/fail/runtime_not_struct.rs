@@ -0,0 +1,6 @@
+#[macros::runtime(block = Block)]
+pub enum Runtime {
+	System,
+}
+
+fn main() {}
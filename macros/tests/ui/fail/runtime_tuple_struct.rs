@@ -0,0 +1,4 @@
+#[macros::runtime(block = Block)]
+pub struct Runtime(u32);
+
+fn main() {}
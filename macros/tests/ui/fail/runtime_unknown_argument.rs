@@ -0,0 +1,6 @@
+#[macros::runtime(block = Block, pallets = 2)]
+pub struct Runtime {
+	system: u32,
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[macros::runtime(block = Block)]
+pub struct Runtime {}
+
+fn main() {}
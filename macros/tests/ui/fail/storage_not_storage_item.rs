@@ -0,0 +1,11 @@
+pub trait Config {
+	type AccountId;
+}
+
+#[macros::storage]
+pub struct Pallet<T: Config> {
+	#[storage(getter = owner)]
+	owner: Option<T::AccountId>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+pub struct StorageValue<V>(V);
+
+#[macros::storage]
+pub struct Pallet {
+	#[storage(getter = count, default = 1, hasher = blake2)]
+	count: StorageValue<u32>,
+}
+
+fn main() {}
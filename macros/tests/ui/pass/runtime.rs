@@ -0,0 +1,152 @@
+#[path = "../../common/mod.rs"]
+mod common;
+
+use common::{support, system};
+use support::{Dispatch, DispatchResult, TakeEvents};
+
+mod types {
+	pub type AccountId = String;
+	pub type Extrinsic = crate::support::Extrinsic<AccountId, crate::runtime::ChainCall>;
+	pub type Header = crate::support::Header<u32>;
+	pub type Block = crate::support::Block<Header, Extrinsic>;
+}
+
+mod counter {
+	use super::*;
+	use support::storage::StorageMap;
+
+	pub trait Config: system::Config {}
+
+	/// The events emitted by the counter pallet.
+	#[derive(Debug, Clone, PartialEq, macros::EventMetadata)]
+	pub enum Event<T: Config> {
+		/// The counter of `who` was set to `value`.
+		Set { who: T::AccountId, value: u32 },
+	}
+
+	/// The errors returned by the counter pallet.
+	#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+	pub enum Error {
+		/// The counter would overflow.
+		#[error("Overflow")]
+		Overflow,
+		/// The block number given to `reset` is zero.
+		#[error("Invalid block")]
+		InvalidBlock,
+	}
+
+	#[macros::storage]
+	#[derive(Debug, Clone, PartialEq)]
+	pub struct Pallet<T: Config> {
+		/// The counter of each account.
+		#[storage(getter = counter, setter = set_counter)]
+		counters: StorageMap<T::AccountId, u32>,
+		events: Vec<Event<T>>,
+	}
+
+	#[macros::call(error = Error)]
+	impl<T: Config> Pallet<T>
+	where
+		T: support::HasPallet<Pallet<T>>,
+	{
+		/// Add `amount` to the counter of `who`.
+		///
+		///     counter.add(who="bob", amount=2)
+		#[weight(u64::from(*amount))]
+		pub fn add(&mut self, _caller: T::AccountId, who: T::AccountId, amount: u32) -> DispatchResult {
+			let value = self.counter(&who).checked_add(amount).ok_or(Error::Overflow)?;
+			self.set(who, value);
+			Ok(())
+		}
+
+		/// Reset the counter of the caller, after checking the block number of the runtime.
+		#[call_index(5)]
+		pub fn reset(runtime: &mut T, caller: T::AccountId, at: u32) -> DispatchResult {
+			if at == 0 {
+				return Err(Error::InvalidBlock.into());
+			}
+			let counter: &mut Self = runtime.pallet_mut();
+			counter.set(caller, 0);
+			Ok(())
+		}
+
+		#[skip_call]
+		fn set(&mut self, who: T::AccountId, value: u32) {
+			self.set_counter(who.clone(), value);
+			self.events.push(Event::Set { who, value });
+		}
+	}
+
+	impl<T: Config> support::Hooks<T> for Pallet<T> {}
+
+	impl<T: Config> TakeEvents for Pallet<T> {
+		type Event = Event<T>;
+
+		fn take_events(&mut self) -> Vec<Event<T>> {
+			core::mem::take(&mut self.events)
+		}
+	}
+}
+
+// The runtime is defined away from the root of the crate, next to the types it uses.
+mod runtime {
+	use super::{counter, system, types};
+
+	impl system::Config for Chain {
+		type AccountId = types::AccountId;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ChainEvent;
+	}
+
+	impl counter::Config for Chain {}
+
+	#[derive(Debug, Clone, PartialEq)]
+	#[macros::runtime(block = types::Block, system = system, support = crate::common::support)]
+	pub struct Chain {
+		pub system: system::Pallet<Chain>,
+		pub counter: counter::Pallet<Chain>,
+	}
+}
+
+use runtime::{Chain, ChainCall, ChainEvent};
+
+fn main() {
+	let mut runtime = Chain::new();
+	let alice = "alice".to_string();
+	let call: ChainCall = "counter.add(amount=3, who=alice)".parse().unwrap();
+	assert_eq!(call.to_string(), "counter.add(who: alice, amount: 3)");
+	assert_eq!(support::GetWeight::weight(&call), 3);
+	assert_eq!(call.call_index(), (1, 0));
+
+	runtime.dispatch(alice.clone(), call).unwrap();
+	assert_eq!(runtime.counter.counter(&alice), 3);
+	assert_eq!(
+		runtime.system.events(),
+		&[ChainEvent::counter(counter::Event::Set { who: alice.clone(), value: 3 })]
+	);
+
+	let reset = ChainCall::counter(counter::Call::reset { at: 0 });
+	assert_eq!(reset.call_index(), (1, 5));
+	assert_eq!(runtime.dispatch(alice.clone(), reset), Err("Invalid block"));
+
+	let block = types::Block {
+		header: support::Header { block_number: 1, timestamp: None },
+		extrinsics: vec![support::Extrinsic {
+			caller: alice.clone(),
+			call: ChainCall::counter(counter::Call::add { who: alice.clone(), amount: 2 }),
+		}],
+	};
+	let receipt = runtime.execute_block(block).unwrap();
+	assert_eq!(receipt.total_weight(), 2);
+	assert_eq!(runtime.counter.counter(&alice), 5);
+
+	let metadata = Chain::metadata();
+	assert_eq!(metadata.pallets[0].name, "system");
+	assert_eq!(metadata.pallets[0].errors[0], "Caller is not root");
+	assert_eq!(metadata.pallets[1].errors, vec!["Overflow", "Invalid block"]);
+	assert_eq!(
+		metadata.pallets[1].calls[0].docs,
+		vec!["Add `amount` to the counter of `who`.", "", "    counter.add(who=\"bob\", amount=2)"]
+	);
+}
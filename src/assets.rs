@@ -0,0 +1,540 @@
+use crate::support::{
+	codec::{Decode, Encode},
+	storage::{OptionQuery, StorageDoubleMap, StorageMap},
+	DispatchResult, TakeEvents,
+};
+use num::traits::{CheckedAdd, CheckedSub, Zero};
+
+pub trait Config: crate::system::Config {
+	/// The type used to identify an asset.
+	type AssetId: Ord + Copy;
+	/// The type used to represent the balance of an account for an asset.
+	type Balance: Zero + CheckedSub + CheckedAdd + Copy + Ord;
+}
+
+/// The details of an asset which has been created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetDetails<AccountId, Balance> {
+	/// The account which created the asset, and is allowed to destroy it.
+	pub owner: AccountId,
+	/// The account which is allowed to mint, burn, freeze and thaw the asset.
+	pub admin: AccountId,
+	/// The total amount of this asset held by all accounts.
+	pub supply: Balance,
+	/// The minimum balance an account is allowed to hold, unless it holds nothing at all.
+	pub min_balance: Balance,
+}
+
+/// The `AssetDetails` stored by the pallet for a runtime `T`.
+pub type AssetDetailsOf<T> =
+	AssetDetails<<T as crate::system::Config>::AccountId, <T as Config>::Balance>;
+
+/// The human readable metadata of an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetMetadata {
+	pub name: String,
+	pub symbol: String,
+	/// The number of decimals used when displaying balances of this asset.
+	pub decimals: u8,
+}
+
+impl<AccountId: Encode, Balance: Encode> Encode for AssetDetails<AccountId, Balance> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.owner.encode_to(dest);
+		self.admin.encode_to(dest);
+		self.supply.encode_to(dest);
+		self.min_balance.encode_to(dest);
+	}
+}
+
+impl<AccountId: Decode, Balance: Decode> Decode for AssetDetails<AccountId, Balance> {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some(Self {
+			owner: Decode::decode(input)?,
+			admin: Decode::decode(input)?,
+			supply: Decode::decode(input)?,
+			min_balance: Decode::decode(input)?,
+		})
+	}
+}
+
+impl Encode for AssetMetadata {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.name.encode_to(dest);
+		self.symbol.encode_to(dest);
+		self.decimals.encode_to(dest);
+	}
+}
+
+impl Decode for AssetMetadata {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some(Self {
+			name: Decode::decode(input)?,
+			symbol: Decode::decode(input)?,
+			decimals: Decode::decode(input)?,
+		})
+	}
+}
+
+/// The events emitted by the assets pallet.
+#[derive(Debug, Clone, PartialEq, macros::EventMetadata)]
+pub enum Event<T: Config> {
+	/// The asset `id` was created by `owner`.
+	Created { id: T::AssetId, owner: T::AccountId, admin: T::AccountId },
+	/// The metadata of the asset `id` was updated.
+	MetadataSet { id: T::AssetId },
+	/// `amount` of the asset `id` was minted into the account of `owner`.
+	Issued { id: T::AssetId, owner: T::AccountId, amount: T::Balance },
+	/// `amount` of the asset `id` was burned from the account of `owner`.
+	Burned { id: T::AssetId, owner: T::AccountId, amount: T::Balance },
+	/// `amount` of the asset `id` was transferred from `from` to `to`.
+	Transferred { id: T::AssetId, from: T::AccountId, to: T::AccountId, amount: T::Balance },
+	/// `who` is no longer allowed to transfer the asset `id`.
+	Frozen { id: T::AssetId, who: T::AccountId },
+	/// `who` is allowed to transfer the asset `id` again.
+	Thawed { id: T::AssetId, who: T::AccountId },
+	/// The asset `id` was destroyed.
+	Destroyed { id: T::AssetId },
+}
+
+/// The errors returned by the assets pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+pub enum Error {
+	/// An asset with the same identifier already exists.
+	#[error("Asset already exists")]
+	AssetAlreadyExists,
+	/// There is no asset with the identifier.
+	#[error("Asset does not exist")]
+	AssetDoesNotExist,
+	/// Only the owner of the asset can do this.
+	#[error("Caller is not the owner of the asset")]
+	NotOwner,
+	/// Only the admin of the asset can do this.
+	#[error("Caller is not the admin of the asset")]
+	NotAdmin,
+	/// The account is not allowed to transfer the asset.
+	#[error("Account is frozen")]
+	AccountFrozen,
+	/// The balance would be below the minimum balance of the asset, without being zero.
+	#[error("Balance below minimum")]
+	BalanceBelowMinimum,
+	/// The balance of the account is lower than the amount.
+	#[error("Insufficient balance")]
+	InsufficientBalance,
+	/// The supply or the balance of the beneficiary would overflow.
+	#[error("Overflow in mint")]
+	MintOverflow,
+	/// The balance of the recipient would overflow.
+	#[error("Overflow in transfer")]
+	TransferOverflow,
+	/// The balance would overflow.
+	#[error("Overflow in burn")]
+	BurnOverflow,
+	/// The supply of the asset would go below zero.
+	#[error("Underflow in burn")]
+	BurnUnderflow,
+}
+
+/// This is the Assets Module.
+/// It allows any account to create its own fungible tokens, next to the native currency managed
+/// by the balances pallet.
+#[macros::storage]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pallet<T: Config> {
+	/// The details of every existing asset.
+	assets: StorageMap<T::AssetId, AssetDetailsOf<T>, OptionQuery>,
+	/// The metadata of every existing asset.
+	metadata: StorageMap<T::AssetId, AssetMetadata, OptionQuery>,
+	/// The balance of each account, for each asset.
+	#[storage(default = T::Balance::zero())]
+	balances: StorageDoubleMap<T::AssetId, T::AccountId, T::Balance>,
+	/// The accounts which are not allowed to transfer an asset.
+	#[storage(default = false)]
+	frozen: StorageDoubleMap<T::AssetId, T::AccountId, bool>,
+	/// The events emitted since the runtime last collected them.
+	events: Vec<Event<T>>,
+}
+
+#[macros::call(explicit_call_index, error = Error)]
+impl<T: Config> Pallet<T> {
+	/// Create a new asset with the identifier `id`, owned by the `caller`.
+	/// The `admin` is the account allowed to mint, burn, freeze and thaw the asset.
+	/// This function will return an error if an asset with the same `id` already exists.
+	#[call_index(0)]
+	#[allow(clippy::too_many_arguments)]
+	pub fn create(
+		&mut self,
+		caller: T::AccountId,
+		id: T::AssetId,
+		admin: T::AccountId,
+		min_balance: T::Balance,
+		name: String,
+		symbol: String,
+		decimals: u8,
+	) -> DispatchResult {
+		if self.assets.contains_key(&id) {
+			return Err(Error::AssetAlreadyExists.into());
+		}
+
+		let details = AssetDetails {
+			owner: caller.clone(),
+			admin: admin.clone(),
+			supply: T::Balance::zero(),
+			min_balance,
+		};
+		self.assets.insert(id, details);
+		self.metadata.insert(id, AssetMetadata { name, symbol, decimals });
+		self.deposit_event(Event::Created { id, owner: caller, admin });
+		Ok(())
+	}
+
+	/// Update the metadata of an asset.
+	/// This function should only succeed if the caller is the owner of the asset.
+	#[call_index(1)]
+	pub fn set_metadata(
+		&mut self,
+		caller: T::AccountId,
+		id: T::AssetId,
+		name: String,
+		symbol: String,
+		decimals: u8,
+	) -> DispatchResult {
+		self.ensure_owner(&id, &caller)?;
+		self.metadata.insert(id, AssetMetadata { name, symbol, decimals });
+		self.deposit_event(Event::MetadataSet { id });
+		Ok(())
+	}
+
+	/// Mint `amount` of the asset into the account of `beneficiary`.
+	/// This function should only succeed if the caller is the admin of the asset, and the
+	/// resulting balance is at least the minimum balance of the asset.
+	#[call_index(2)]
+	pub fn mint(
+		&mut self,
+		caller: T::AccountId,
+		id: T::AssetId,
+		beneficiary: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let details = self.ensure_admin(&id, &caller)?;
+
+		let new_supply = details.supply.checked_add(&amount).ok_or(Error::MintOverflow)?;
+		let new_balance =
+			self.balance(id, &beneficiary).checked_add(&amount).ok_or(Error::MintOverflow)?;
+		if new_balance < details.min_balance {
+			return Err(Error::BalanceBelowMinimum.into());
+		}
+
+		self.set_balance(id, &beneficiary, new_balance);
+		self.set_supply(id, new_supply);
+		self.deposit_event(Event::Issued { id, owner: beneficiary, amount });
+		Ok(())
+	}
+
+	/// Burn `amount` of the asset from the account of `who`.
+	/// This function should only succeed if the caller is the admin of the asset. If the
+	/// remaining balance is below the minimum balance of the asset, it is burned as well.
+	#[call_index(3)]
+	pub fn burn(
+		&mut self,
+		caller: T::AccountId,
+		id: T::AssetId,
+		who: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let details = self.ensure_admin(&id, &caller)?;
+
+		let mut new_balance =
+			self.balance(id, &who).checked_sub(&amount).ok_or(Error::InsufficientBalance)?;
+		let mut burned = amount;
+		if new_balance < details.min_balance {
+			burned = burned.checked_add(&new_balance).ok_or(Error::BurnOverflow)?;
+			new_balance = T::Balance::zero();
+		}
+
+		let new_supply = details.supply.checked_sub(&burned).ok_or(Error::BurnUnderflow)?;
+		self.set_balance(id, &who, new_balance);
+		self.set_supply(id, new_supply);
+		self.deposit_event(Event::Burned { id, owner: who, amount: burned });
+		Ok(())
+	}
+
+	/// Transfer `amount` of the asset from the `caller` to `to`.
+	/// This function will return an error if the caller is frozen, does not have enough balance,
+	/// or if either account would be left with less than the minimum balance of the asset.
+	#[call_index(4)]
+	pub fn transfer(
+		&mut self,
+		caller: T::AccountId,
+		id: T::AssetId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let details = self.asset(&id).ok_or(Error::AssetDoesNotExist)?;
+		if self.is_frozen(id, &caller) {
+			return Err(Error::AccountFrozen.into());
+		}
+
+		let new_caller_balance =
+			self.balance(id, &caller).checked_sub(&amount).ok_or(Error::InsufficientBalance)?;
+		// The credit is computed on top of the debit, so that a transfer to oneself leaves the
+		// balance, and so the supply, unchanged.
+		let to_balance = if to == caller { new_caller_balance } else { self.balance(id, &to) };
+		let new_to_balance = to_balance.checked_add(&amount).ok_or(Error::TransferOverflow)?;
+
+		let below_minimum = |balance: T::Balance| {
+			!balance.is_zero() && balance < details.min_balance
+		};
+		if below_minimum(new_caller_balance) || below_minimum(new_to_balance) {
+			return Err(Error::BalanceBelowMinimum.into());
+		}
+
+		self.set_balance(id, &caller, new_caller_balance);
+		self.set_balance(id, &to, new_to_balance);
+		self.deposit_event(Event::Transferred { id, from: caller, to, amount });
+		Ok(())
+	}
+
+	/// Prevent `who` from transferring the asset.
+	/// This function should only succeed if the caller is the admin of the asset.
+	#[call_index(5)]
+	pub fn freeze(&mut self, caller: T::AccountId, id: T::AssetId, who: T::AccountId) -> DispatchResult {
+		self.ensure_admin(&id, &caller)?;
+		self.frozen.insert(id, who.clone(), true);
+		self.deposit_event(Event::Frozen { id, who });
+		Ok(())
+	}
+
+	/// Allow a frozen account `who` to transfer the asset again.
+	/// This function should only succeed if the caller is the admin of the asset.
+	#[call_index(6)]
+	pub fn thaw(&mut self, caller: T::AccountId, id: T::AssetId, who: T::AccountId) -> DispatchResult {
+		self.ensure_admin(&id, &caller)?;
+		self.frozen.remove(&id, &who);
+		self.deposit_event(Event::Thawed { id, who });
+		Ok(())
+	}
+
+	/// Destroy an asset, removing its metadata and all the balances held in it.
+	/// This function should only succeed if the caller is the owner of the asset.
+	#[call_index(7)]
+	pub fn destroy(&mut self, caller: T::AccountId, id: T::AssetId) -> DispatchResult {
+		self.ensure_owner(&id, &caller)?;
+
+		self.assets.remove(&id);
+		self.metadata.remove(&id);
+		self.balances.remove_prefix(&id);
+		self.frozen.remove_prefix(&id);
+		self.deposit_event(Event::Destroyed { id });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Get the details (if any) of an asset.
+	pub fn asset(&self, id: &T::AssetId) -> Option<&AssetDetails<T::AccountId, T::Balance>> {
+		self.assets.get_ref(id)
+	}
+
+	/// Get the metadata (if any) of an asset.
+	pub fn metadata(&self, id: &T::AssetId) -> Option<&AssetMetadata> {
+		self.metadata.get_ref(id)
+	}
+
+	/// Get the balance of an account `who` for the asset `id`.
+	/// If the account has no stored balance, we return zero.
+	pub fn balance(&self, id: T::AssetId, who: &T::AccountId) -> T::Balance {
+		self.balances.get(&id, who)
+	}
+
+	/// Check if the account `who` is frozen for the asset `id`.
+	pub fn is_frozen(&self, id: T::AssetId, who: &T::AccountId) -> bool {
+		self.frozen.get(&id, who)
+	}
+
+	/// Set the balance of an account `who` for the asset `id`.
+	/// A zero balance is removed from storage.
+	fn set_balance(&mut self, id: T::AssetId, who: &T::AccountId, amount: T::Balance) {
+		if amount.is_zero() {
+			self.balances.remove(&id, who);
+		} else {
+			self.balances.insert(id, who.clone(), amount);
+		}
+	}
+
+	/// Set the total supply of the asset `id`.
+	fn set_supply(&mut self, id: T::AssetId, supply: T::Balance) {
+		if let Some(mut details) = self.asset(&id).cloned() {
+			details.supply = supply;
+			self.assets.insert(id, details);
+		}
+	}
+
+	/// Check that the asset `id` exists and that `who` is its owner.
+	fn ensure_owner(
+		&self,
+		id: &T::AssetId,
+		who: &T::AccountId,
+	) -> Result<AssetDetails<T::AccountId, T::Balance>, &'static str> {
+		let details = self.asset(id).ok_or(Error::AssetDoesNotExist)?;
+		if &details.owner != who {
+			return Err(Error::NotOwner.into());
+		}
+		Ok(details.clone())
+	}
+
+	/// Check that the asset `id` exists and that `who` is its admin.
+	fn ensure_admin(
+		&self,
+		id: &T::AssetId,
+		who: &T::AccountId,
+	) -> Result<AssetDetails<T::AccountId, T::Balance>, &'static str> {
+		let details = self.asset(id).ok_or(Error::AssetDoesNotExist)?;
+		if &details.admin != who {
+			return Err(Error::NotAdmin.into());
+		}
+		Ok(details.clone())
+	}
+
+	/// Emit an event, to be collected by the runtime.
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T> for Pallet<T> {}
+
+impl<T: Config> TakeEvents for Pallet<T> {
+	type Event = Event<T>;
+
+	fn take_events(&mut self) -> Vec<Event<T>> {
+		core::mem::take(&mut self.events)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Event;
+	use crate::support::TakeEvents;
+
+	#[derive(Debug, Clone, PartialEq)]
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+	}
+
+	impl super::Config for TestConfig {
+		type AssetId = u32;
+		type Balance = u128;
+	}
+
+	fn new_asset() -> super::Pallet<TestConfig> {
+		let mut assets = super::Pallet::<TestConfig>::new();
+		let result = assets.create("alice", 1, "admin", 10, "Stable".into(), "STB".into(), 6);
+		assert_eq!(result, Ok(()));
+		assets
+	}
+
+	#[test]
+	fn create_asset() {
+		let mut assets = new_asset();
+
+		let details = assets.asset(&1).unwrap();
+		assert_eq!(details.owner, "alice");
+		assert_eq!(details.admin, "admin");
+		assert_eq!(assets.metadata(&1).unwrap().decimals, 6);
+
+		let result = assets.create("bob", 1, "bob", 1, "Other".into(), "OTH".into(), 0);
+		assert_eq!(result, Err("Asset already exists"));
+
+		let result = assets.set_metadata("bob", 1, "Other".into(), "OTH".into(), 0);
+		assert_eq!(result, Err("Caller is not the owner of the asset"));
+		assert_eq!(assets.set_metadata("alice", 1, "Stable".into(), "STB".into(), 2), Ok(()));
+		assert_eq!(assets.metadata(&1).unwrap().decimals, 2);
+	}
+
+	#[test]
+	fn mint_and_burn() {
+		let mut assets = new_asset();
+
+		assert_eq!(assets.mint("alice", 1, "bob", 100), Err("Caller is not the admin of the asset"));
+		assert_eq!(assets.mint("admin", 2, "bob", 100), Err("Asset does not exist"));
+		assert_eq!(assets.mint("admin", 1, "bob", 5), Err("Balance below minimum"));
+		assert_eq!(assets.mint("admin", 1, "bob", 100), Ok(()));
+		assert_eq!(assets.balance(1, &"bob"), 100);
+		assert_eq!(assets.asset(&1).unwrap().supply, 100);
+
+		assert_eq!(assets.burn("admin", 1, "bob", 40), Ok(()));
+		assert_eq!(assets.balance(1, &"bob"), 60);
+
+		// The remaining 5 are below the minimum balance, so they are burned as well.
+		assert_eq!(assets.burn("admin", 1, "bob", 55), Ok(()));
+		assert_eq!(assets.balance(1, &"bob"), 0);
+		assert_eq!(assets.asset(&1).unwrap().supply, 0);
+		assert_eq!(
+			assets.take_events(),
+			vec![
+				Event::Created { id: 1, owner: "alice", admin: "admin" },
+				Event::Issued { id: 1, owner: "bob", amount: 100 },
+				Event::Burned { id: 1, owner: "bob", amount: 40 },
+				Event::Burned { id: 1, owner: "bob", amount: 60 },
+			]
+		);
+	}
+
+	#[test]
+	fn transfer_asset() {
+		let mut assets = new_asset();
+		let _ = assets.mint("admin", 1, "bob", 100);
+
+		assert_eq!(assets.transfer("bob", 1, "charlie", 101), Err("Insufficient balance"));
+		assert_eq!(assets.transfer("bob", 1, "charlie", 5), Err("Balance below minimum"));
+		assert_eq!(assets.transfer("bob", 1, "charlie", 95), Err("Balance below minimum"));
+		assert_eq!(assets.transfer("bob", 1, "charlie", 30), Ok(()));
+		assert_eq!(assets.transfer("bob", 1, "charlie", 70), Ok(()));
+		assert_eq!(assets.balance(1, &"bob"), 0);
+		assert_eq!(assets.balance(1, &"charlie"), 100);
+	}
+
+	#[test]
+	fn transfer_asset_to_self() {
+		let mut assets = new_asset();
+		let _ = assets.mint("admin", 1, "bob", 100);
+
+		assert_eq!(assets.transfer("bob", 1, "bob", 60), Ok(()));
+		assert_eq!(assets.balance(1, &"bob"), 100);
+		assert_eq!(assets.transfer("bob", 1, "bob", 101), Err("Insufficient balance"));
+		assert_eq!(assets.asset(&1).unwrap().supply, 100);
+	}
+
+	#[test]
+	fn freeze_and_thaw() {
+		let mut assets = new_asset();
+		let _ = assets.mint("admin", 1, "bob", 100);
+
+		assert_eq!(assets.freeze("bob", 1, "bob"), Err("Caller is not the admin of the asset"));
+		assert_eq!(assets.freeze("admin", 1, "bob"), Ok(()));
+		assert_eq!(assets.transfer("bob", 1, "charlie", 50), Err("Account is frozen"));
+
+		assert_eq!(assets.thaw("admin", 1, "bob"), Ok(()));
+		assert_eq!(assets.transfer("bob", 1, "charlie", 50), Ok(()));
+	}
+
+	#[test]
+	fn destroy_asset() {
+		let mut assets = new_asset();
+		let _ = assets.mint("admin", 1, "bob", 100);
+		let _ = assets.freeze("admin", 1, "bob");
+
+		assert_eq!(assets.destroy("admin", 1), Err("Caller is not the owner of the asset"));
+		assert_eq!(assets.destroy("alice", 1), Ok(()));
+		assert_eq!(assets.asset(&1), None);
+		assert_eq!(assets.metadata(&1), None);
+		assert_eq!(assets.balance(1, &"bob"), 0);
+		assert!(!assets.is_frozen(1, &"bob"));
+	}
+}
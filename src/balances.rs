@@ -1,22 +1,92 @@
-use crate::support::Dispatch;
-use std::collections::BTreeMap; // we are using this rust collection to store balances, in real blockchain balances are stored in a database
+use crate::support::{
+	storage::{StorageDoubleMap, StorageMap},
+	Currency, DispatchResult, TakeEvents,
+};
+use core::fmt::Debug;
 use num::traits::{CheckedAdd, CheckedSub, Zero};
 
-pub trait Config: crate::system::Config {
-	type Balance: Zero + CheckedSub + CheckedAdd + Copy;
+/// The configuration of an instance `I` of the balances pallet. A runtime can include several
+/// independent instances, like `Pallet<Runtime, support::Instance1>`, each with its own `Config`.
+/// The default instance is `()`.
+pub trait Config<I = ()>: crate::system::Config {
+	type Balance: Zero + CheckedSub + CheckedAdd + Copy + Debug + PartialEq;
 }
 
-#[derive(Debug)]
+/// The events emitted by the balances pallet.
+#[derive(Debug, Clone, PartialEq, macros::EventMetadata)]
+pub enum Event<T: Config<I>, I = ()> {
+	/// `amount` was transferred from `from` to `to`.
+	Transfer { from: T::AccountId, to: T::AccountId, amount: T::Balance },
+	/// `owner` allowed `spender` to transfer up to `amount` on their behalf.
+	Approval { owner: T::AccountId, spender: T::AccountId, amount: T::Balance },
+	/// `amount` was added to the free balance of `who`.
+	Deposit { who: T::AccountId, amount: T::Balance },
+	/// `amount` was removed from the free balance of `who`.
+	Withdraw { who: T::AccountId, amount: T::Balance },
+	/// `amount` of the free balance of `who` was reserved.
+	Reserved { who: T::AccountId, amount: T::Balance },
+	/// `amount` of the reserved balance of `who` was freed.
+	Unreserved { who: T::AccountId, amount: T::Balance },
+}
+
+/// The errors returned by the balances pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+pub enum Error {
+	/// The free balance of the account is lower than the amount.
+	#[error("Insufficient balance")]
+	InsufficientBalance,
+	/// The reserved balance of the account is lower than the amount.
+	#[error("Insufficient reserved balance")]
+	InsufficientReservedBalance,
+	/// The spender is allowed to transfer less than the amount.
+	#[error("Insufficient allowance")]
+	InsufficientAllowance,
+	/// The allowance would be decreased below zero.
+	#[error("Allowance below zero")]
+	AllowanceBelowZero,
+	/// The balance of the recipient would overflow.
+	#[error("Overflow in transfer")]
+	TransferOverflow,
+	/// The allowance would overflow.
+	#[error("Overflow in allowance")]
+	AllowanceOverflow,
+	/// The balance of the account would overflow.
+	#[error("Overflow in deposit")]
+	DepositOverflow,
+	/// The reserved balance of the account would overflow.
+	#[error("Overflow in reserve")]
+	ReserveOverflow,
+	/// The free balance of the account would overflow.
+	#[error("Overflow in unreserve")]
+	UnreserveOverflow,
+}
+
+#[macros::storage]
+#[derive(Debug, Clone, PartialEq)]
 //Here we want to store balance of each user
-pub struct Pallet<T:Config> {
- 	balances: BTreeMap<T::AccountId, T::Balance>,
+pub struct Pallet<T:Config<I>, I = ()> {
+	/// Get the balance of an account `who`.
+	/// If the account has no stored balance, we return zero.
+	#[storage(getter = balance, default = T::Balance::zero())]
+ 	balances: StorageMap<T::AccountId, T::Balance>,
+	/// Get the reserved balance of an account `who`.
+	/// If the account has no reserved balance, we return zero.
+	#[storage(getter = reserved, default = T::Balance::zero())]
+	reserved: StorageMap<T::AccountId, T::Balance>,
+	/// Get the amount `spender` is allowed to transfer on behalf of `owner`.
+	/// If no allowance was given, we return zero.
+	#[storage(getter = allowance, default = T::Balance::zero())]
+	allowances: StorageDoubleMap<T::AccountId, T::AccountId, T::Balance>,
+	/// The events emitted since the runtime last collected them.
+	events: Vec<Event<T, I>>,
 }
 
-#[macros::call]
-impl<T: Config>Pallet<T> {
+#[macros::call(explicit_call_index, error = Error)]
+impl<T: Config<I>, I> Pallet<T, I> {
         /// Transfer `amount` from one account to another.
 	/// This function verifies that `from` has at least `amount` balance to transfer,
 	/// and that no mathematical overflows occur.
+	#[call_index(0)]
 	pub fn transfer(
 		&mut self,
 		caller: T::AccountId,
@@ -24,57 +94,196 @@ impl<T: Config>Pallet<T> {
 		amount: T::Balance,
 	) -> Result<(), &'static str> {
         let caller_balance = self.balance(&caller);
-        let to_balance = self.balance(&to);
 
         let new_caller_balance = caller_balance.checked_sub(&amount)
-            .ok_or("Insufficient balance")?;
+            .ok_or(Error::InsufficientBalance)?;
 
+        // The credit is computed on top of the debit, so that a transfer to oneself leaves the
+        // balance unchanged.
+        let to_balance = if to == caller { new_caller_balance } else { self.balance(&to) };
         let new_to_balance = to_balance.checked_add(&amount)
-            .ok_or("Overflow in transfer")?;
+            .ok_or(Error::TransferOverflow)?;
 
         self.set_balance(&caller, new_caller_balance);
         self.set_balance(&to, new_to_balance);
+		self.deposit_event(Event::Transfer { from: caller, to, amount });
 
 		Ok(())
 	}
-}
 
+	/// Allow `spender` to transfer up to `amount` from the balance of `caller`.
+	/// This overwrites any allowance previously given to `spender`.
+	#[call_index(1)]
+	pub fn approve(
+		&mut self,
+		caller: T::AccountId,
+		spender: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		self.set_allowance(caller, spender, amount);
+		Ok(())
+	}
+
+	/// Transfer `amount` from `owner` to `to` on behalf of `owner`.
+	/// This function verifies that `caller` has been allowed to spend at least `amount`,
+	/// and consumes that much of the allowance.
+	#[call_index(2)]
+	pub fn transfer_from(
+		&mut self,
+		caller: T::AccountId,
+		owner: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let new_allowance = self.allowance(&owner, &caller)
+			.checked_sub(&amount)
+			.ok_or(Error::InsufficientAllowance)?;
+
+		self.transfer(owner.clone(), to, amount)?;
+		self.set_allowance(owner, caller, new_allowance);
+
+		Ok(())
+	}
+
+	/// Increase the allowance of `spender` on the balance of `caller` by `amount`.
+	#[call_index(3)]
+	pub fn increase_allowance(
+		&mut self,
+		caller: T::AccountId,
+		spender: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let new_allowance = self.allowance(&caller, &spender)
+			.checked_add(&amount)
+			.ok_or(Error::AllowanceOverflow)?;
+
+		self.set_allowance(caller, spender, new_allowance);
+		Ok(())
+	}
+
+	/// Decrease the allowance of `spender` on the balance of `caller` by `amount`.
+	/// This function will return an error if the allowance would go below zero.
+	#[call_index(4)]
+	pub fn decrease_allowance(
+		&mut self,
+		caller: T::AccountId,
+		spender: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let new_allowance = self.allowance(&caller, &spender)
+			.checked_sub(&amount)
+			.ok_or(Error::AllowanceBelowZero)?;
 
-impl <T:Config> Pallet<T> where{
-	/// Create a new instance of the balances module.
-	pub fn new() -> Self {
-		Self { balances: BTreeMap::new() }
+		self.set_allowance(caller, spender, new_allowance);
+		Ok(())
 	}
+}
 
+
+impl <T:Config<I>, I> Pallet<T, I> where{
 	/// Set the balance of an account `who` to some `amount`.
 	pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) {
         self.balances.insert(who.clone(), amount);
 	}
 
-	/// Get the balance of an account `who`.
-	/// If the account has no stored balance, we return zero.
-	pub fn balance(&self, who: &T::AccountId) -> T::Balance {
-        *self.balances.get(who).unwrap_or(&T::Balance::zero())
+	/// Set the amount `spender` is allowed to transfer on behalf of `owner`.
+	fn set_allowance(&mut self, owner: T::AccountId, spender: T::AccountId, amount: T::Balance) {
+		self.allowances.insert(owner.clone(), spender.clone(), amount);
+		self.deposit_event(Event::Approval { owner, spender, amount });
+	}
+
+	/// Emit an event, to be collected by the runtime.
+	fn deposit_event(&mut self, event: Event<T, I>) {
+		self.events.push(event);
+	}
+}
+
+/// The balances pallet is the native `Currency` of the runtime, which other pallets can use to
+/// move or lock funds of their users.
+impl<T: Config<I>, I> Currency<T::AccountId> for Pallet<T, I> {
+	type Balance = T::Balance;
+
+	fn free_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.balance(who)
+	}
+
+	fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.reserved(who)
+	}
+
+	fn transfer(&mut self, from: &T::AccountId, to: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		Pallet::transfer(self, from.clone(), to.clone(), amount)
+	}
+
+	fn withdraw(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_balance = self.balance(who).checked_sub(&amount).ok_or(Error::InsufficientBalance)?;
+		self.set_balance(who, new_balance);
+		self.deposit_event(Event::Withdraw { who: who.clone(), amount });
+		Ok(())
+	}
+
+	fn deposit(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_balance = self.balance(who).checked_add(&amount).ok_or(Error::DepositOverflow)?;
+		self.set_balance(who, new_balance);
+		self.deposit_event(Event::Deposit { who: who.clone(), amount });
+		Ok(())
+	}
+
+	fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_balance = self.balance(who).checked_sub(&amount).ok_or(Error::InsufficientBalance)?;
+		let new_reserved = self.reserved(who).checked_add(&amount).ok_or(Error::ReserveOverflow)?;
+		self.set_balance(who, new_balance);
+		self.reserved.insert(who.clone(), new_reserved);
+		self.deposit_event(Event::Reserved { who: who.clone(), amount });
+		Ok(())
 	}
 
+	fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_reserved =
+			self.reserved(who).checked_sub(&amount).ok_or(Error::InsufficientReservedBalance)?;
+		let new_balance = self.balance(who).checked_add(&amount).ok_or(Error::UnreserveOverflow)?;
+		self.reserved.insert(who.clone(), new_reserved);
+		self.set_balance(who, new_balance);
+		self.deposit_event(Event::Unreserved { who: who.clone(), amount });
+		Ok(())
+	}
+}
+
+impl<T: Config<I>, I> crate::support::Hooks<T> for Pallet<T, I> {}
+
+impl<T: Config<I>, I> TakeEvents for Pallet<T, I> {
+	type Event = Event<T, I>;
+
+	fn take_events(&mut self) -> Vec<Event<T, I>> {
+		core::mem::take(&mut self.events)
+	}
 }
 
 #[cfg(test)]
 mod tests {
 
+    use crate::support::{Instance1, TakeEvents};
     use crate::system;
+    use super::Event;
+
+    #[derive(Debug, Clone, PartialEq)]
     struct TestConfig;
 
     impl system::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = ();
     }
 
     impl super::Config for TestConfig {
         type Balance = u128;
     }
 
+    impl super::Config<Instance1> for TestConfig {
+        type Balance = u64;
+    }
+
     #[test]
 
     fn init_balances() {
@@ -131,4 +340,145 @@ mod tests {
         assert_eq!(balances.balance(&bob), u128::MAX);
     }
 
+    #[test]
+    fn transfer_to_self() {
+        use crate::support::Currency;
+
+        let alice = "alice".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        balances.set_balance(&alice, 100);
+        assert_eq!(balances.transfer(alice.clone(), alice.clone(), 60), Ok(()));
+        assert_eq!(balances.balance(&alice), 100);
+        assert_eq!(Currency::transfer(&mut balances, &alice, &alice, 100), Ok(()));
+        assert_eq!(balances.balance(&alice), 100);
+        assert_eq!(balances.transfer(alice.clone(), alice.clone(), 101), Err("Insufficient balance"));
+    }
+
+    #[test]
+    fn transfer_from_with_allowance() {
+        let alice = "alice".to_string();
+        let bob = "bob".to_string();
+        let charlie = "charlie".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        balances.set_balance(&alice, 100);
+        let result = balances.transfer_from(bob.clone(), alice.clone(), charlie.clone(), 10);
+        assert_eq!(result, Err("Insufficient allowance"));
+
+        assert_eq!(balances.approve(alice.clone(), bob.clone(), 50), Ok(()));
+        assert_eq!(balances.transfer_from(bob.clone(), alice.clone(), charlie.clone(), 30), Ok(()));
+        assert_eq!(balances.balance(&alice), 70);
+        assert_eq!(balances.balance(&charlie), 30);
+        assert_eq!(balances.allowance(&alice, &bob), 20);
+
+        let result = balances.transfer_from(bob.clone(), alice.clone(), charlie.clone(), 21);
+        assert_eq!(result, Err("Insufficient allowance"));
+        assert_eq!(balances.allowance(&alice, &bob), 20);
+    }
+
+    #[test]
+    fn transfers_emit_events() {
+        let alice = "alice".to_string();
+        let bob = "bob".to_string();
+        let charlie = "charlie".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        balances.set_balance(&alice, 100);
+        assert!(balances.transfer(alice.clone(), bob.clone(), 110).is_err());
+        assert!(balances.take_events().is_empty());
+
+        assert_eq!(balances.transfer(alice.clone(), bob.clone(), 40), Ok(()));
+        assert_eq!(balances.approve(alice.clone(), bob.clone(), 50), Ok(()));
+        assert_eq!(balances.transfer_from(bob.clone(), alice.clone(), charlie.clone(), 30), Ok(()));
+        assert_eq!(
+            balances.take_events(),
+            vec![
+                Event::Transfer { from: alice.clone(), to: bob.clone(), amount: 40 },
+                Event::Approval { owner: alice.clone(), spender: bob.clone(), amount: 50 },
+                Event::Transfer { from: alice.clone(), to: charlie, amount: 30 },
+                Event::Approval { owner: alice, spender: bob, amount: 20 },
+            ]
+        );
+    }
+
+    #[test]
+    fn transfer_from_insufficient_balance_keeps_allowance() {
+        let alice = "alice".to_string();
+        let bob = "bob".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        balances.set_balance(&alice, 10);
+        let _ = balances.approve(alice.clone(), bob.clone(), 50);
+
+        let result = balances.transfer_from(bob.clone(), alice.clone(), bob.clone(), 20);
+        assert_eq!(result, Err("Insufficient balance"));
+        assert_eq!(balances.allowance(&alice, &bob), 50);
+    }
+
+    #[test]
+    fn increase_and_decrease_allowance() {
+        let alice = "alice".to_string();
+        let bob = "bob".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        assert_eq!(balances.increase_allowance(alice.clone(), bob.clone(), 40), Ok(()));
+        assert_eq!(balances.increase_allowance(alice.clone(), bob.clone(), 10), Ok(()));
+        assert_eq!(balances.allowance(&alice, &bob), 50);
+
+        assert_eq!(balances.decrease_allowance(alice.clone(), bob.clone(), 20), Ok(()));
+        assert_eq!(balances.allowance(&alice, &bob), 30);
+
+        let result = balances.decrease_allowance(alice.clone(), bob.clone(), 31);
+        assert_eq!(result, Err("Allowance below zero"));
+        assert_eq!(balances.allowance(&alice, &bob), 30);
+    }
+
+    #[test]
+    fn currency_reserve_and_unreserve() {
+        use crate::support::Currency;
+
+        let alice = "alice".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        balances.set_balance(&alice, 100);
+        assert_eq!(balances.reserve(&alice, 30), Ok(()));
+        assert_eq!(balances.free_balance(&alice), 70);
+        assert_eq!(balances.reserved_balance(&alice), 30);
+
+        assert_eq!(balances.reserve(&alice, 71), Err("Insufficient balance"));
+        assert_eq!(balances.unreserve(&alice, 31), Err("Insufficient reserved balance"));
+
+        assert_eq!(balances.unreserve(&alice, 30), Ok(()));
+        assert_eq!(balances.free_balance(&alice), 100);
+        assert_eq!(balances.reserved_balance(&alice), 0);
+    }
+
+    #[test]
+    fn currency_withdraw_and_deposit() {
+        use crate::support::Currency;
+
+        let alice = "alice".to_string();
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+
+        assert_eq!(balances.withdraw(&alice, 1), Err("Insufficient balance"));
+        assert_eq!(balances.deposit(&alice, 50), Ok(()));
+        assert_eq!(balances.withdraw(&alice, 20), Ok(()));
+        assert_eq!(balances.free_balance(&alice), 30);
+    }
+
+    #[test]
+    fn instances_have_distinct_prefixes() {
+        let default: super::Pallet<TestConfig> = super::Pallet::new();
+        let instance: super::Pallet<TestConfig, Instance1> = super::Pallet::new();
+
+        assert_eq!(default.balances.prefix(), "ironledger_rust_state_machine::balances::balances");
+        assert_eq!(
+            instance.balances.prefix(),
+            "ironledger_rust_state_machine::balances::Instance1::balances"
+        );
+        let alice = "alice".to_string();
+        assert_ne!(default.balances.raw_key(&alice), instance.balances.raw_key(&alice));
+    }
+
 }
\ No newline at end of file
@@ -1,48 +1,163 @@
-use crate::support::Dispatch;
-
+mod assets;
 mod balances;
 mod proof_of_existence;
 mod support;
 mod system;
+mod utility;
 
 mod types {
     use crate::support;
 
     pub type AccountId = String;
     pub type Balance = u128;
+    pub type StableBalance = u64;
     pub type BlockNumber = u32;
     pub type Nonce = u32;
+    pub type AssetId = u32;
     pub type Extrinsic = support::Extrinsic<AccountId, crate::RuntimeCall>;
     pub type Header = support::Header<BlockNumber>;
     pub type Block = support::Block<Header, Extrinsic>;
-    pub type Content = &'static str; // For simplicity, we use String as the content type.
+    pub type Content = support::H256;
+
+    /// The root account of the runtime, which can change its storage with the `set_storage` and
+    /// `kill_storage` calls of the system pallet, like with
+    /// `dry-run root "system.kill_storage(keys=[..])"`. No one else can make these calls.
+    pub const ROOT: &str = "root";
 }
 
 impl system::Config for Runtime {
     type AccountId = types::AccountId;
     type BlockNumber = types::BlockNumber;
     type Nonce = types::Nonce;
+    type RuntimeEvent = RuntimeEvent;
+
+    fn root() -> Option<types::AccountId> {
+        Some(types::ROOT.to_string())
+    }
 }
 
 impl balances::Config for Runtime {
     type Balance = types::Balance;
 }
 
+// A second, independent token, with its own balances and calls.
+impl balances::Config<support::Instance1> for Runtime {
+    type Balance = types::StableBalance;
+}
+
+impl assets::Config for Runtime {
+    type AssetId = types::AssetId;
+    type Balance = types::Balance;
+}
+
 impl proof_of_existence::Config for Runtime {
-    type Content = types::Content; // For simplicity, we use String as the content type.
+    type Content = types::Content;
+    type Hashing = support::BlakeTwo256;
+    type Currency = balances::Pallet<Runtime>;
+    const CLAIM_DEPOSIT: types::Balance = 5;
+}
+
+impl utility::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
 }
 
-#[derive(Debug)]
-#[macros::runtime]
+#[derive(Debug, Clone, PartialEq)]
+#[macros::runtime(block = types::Block)]
 // This is our main Runtime.
 // It accumulates all of the different pallets we want to use.
 pub struct Runtime {
     system: system::Pallet<Runtime>,
     balances: balances::Pallet<Runtime>,
+    assets: assets::Pallet<Runtime>,
     proof_of_existence: proof_of_existence::Pallet<Runtime>,
+    utility: utility::Pallet<Runtime>,
+    stable: balances::Pallet<Runtime, support::Instance1>,
+}
+
+/// The current time of this machine, which the node puts in the header of the blocks it builds.
+/// The runtime itself never reads the clock.
+fn now() -> Option<support::Moment> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(now.as_secs())
+}
+
+/// Build the extrinsic which claims `claim` on behalf of `caller`.
+fn create_claim_extrinsic(caller: types::AccountId, claim: types::Content) -> types::Extrinsic {
+    support::Extrinsic {
+        caller,
+        call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+            claim,
+            expires_in: None,
+        }),
+    }
+}
+
+/// `claim <caller> <file>`: hashes a local file and claims it on behalf of `caller` in a new
+/// chain, then prints the resulting claim.
+fn claim_file(caller: &str, path: &str) {
+    let claim = proof_of_existence::Pallet::<Runtime>::hash_file(path).unwrap_or_else(|e| {
+        eprintln!("Could not read {}: {}", path, e);
+        std::process::exit(1);
+    });
+
+    // Genesis state: the caller only needs to afford the claim deposit.
+    let mut runtime = Runtime::new();
+    let deposit = <Runtime as proof_of_existence::Config>::CLAIM_DEPOSIT;
+    runtime.balances.set_balance(&caller.to_string(), deposit);
+
+    let block = types::Block {
+        header: support::Header { block_number: 1, timestamp: now() },
+        extrinsics: vec![create_claim_extrinsic(caller.to_string(), claim)],
+    };
+    let receipt = runtime
+        .execute_block(block)
+        .expect("Block execution failed");
+    if let Some(Err(e)) = receipt.failed().map(|extrinsic| extrinsic.result).next() {
+        eprintln!("Could not claim {}: {}", path, e);
+        std::process::exit(1);
+    }
+
+    println!("Content hash: {}", claim);
+    println!("{:?}", runtime.proof_of_existence.claim_info(&claim));
+}
+
+/// `dry-run <caller> <call>`: parses a call written like `balances.transfer(to=bob, amount=10)`
+/// and dry runs it on behalf of `caller` in a new chain where they own 100 tokens, then prints its
+/// outcome.
+fn dry_run_call(caller: &str, call: &str) {
+    let call: RuntimeCall = call.parse().unwrap_or_else(|e| {
+        eprintln!("Could not parse the call: {}", e);
+        std::process::exit(1);
+    });
+
+    let mut runtime = Runtime::new();
+    runtime.balances.set_balance(&caller.to_string(), 100);
+
+    println!("Call: {}", call);
+    let outcome = runtime.dry_run(support::Extrinsic { caller: caller.to_string(), call });
+    println!("Result: {:?}", outcome.result);
+    println!("Declared weight: {}", outcome.weight);
+    for event in outcome.events {
+        println!("Event: {:?}", event);
+    }
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, caller, path] = args.as_slice() && command == "claim" {
+        claim_file(caller, path);
+        return;
+    }
+    if let [command, caller, call] = args.as_slice() && command == "dry-run" {
+        dry_run_call(caller, call);
+        return;
+    }
+    // `metadata`: prints the description of the calls, events and errors of the runtime as JSON.
+    if let [command] = args.as_slice() && command == "metadata" {
+        println!("{}", Runtime::metadata().to_json());
+        return;
+    }
+
     let mut runtime = Runtime::new();
 
     //Genesis state
@@ -54,7 +169,7 @@ fn main() {
     runtime.balances.set_balance(&rajkumar, 100);
 
     let block_1 = types::Block {
-        header: support::Header { block_number: 1 },
+        header: support::Header { block_number: 1, timestamp: now() },
         extrinsics: vec![
             support::Extrinsic {
                 caller: rajkumar.clone(),
@@ -74,29 +189,480 @@ fn main() {
     };
 
     let block_2 = types::Block {
-        header: support::Header { block_number: 2 },
+        header: support::Header { block_number: 2, timestamp: now() },
+        extrinsics: vec![
+            create_claim_extrinsic(
+                dayitva.clone(),
+                proof_of_existence::Pallet::<Runtime>::hash_content(b"UNISWAP DOCS"),
+            ),
+            create_claim_extrinsic(
+                rajkumar,
+                proof_of_existence::Pallet::<Runtime>::hash_content(b"UNISWAP V3 DOCS"),
+            ),
+        ],
+    };
+
+    let block_3 = types::Block {
+        header: support::Header { block_number: 3, timestamp: now() },
         extrinsics: vec![
             support::Extrinsic {
-                caller: dayitva.clone(),
-                call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-                    claim: "UNISWAP DOCS",
+                caller: aditya.clone(),
+                call: RuntimeCall::assets(assets::Call::create {
+                    id: 1,
+                    admin: aditya.clone(),
+                    min_balance: 1,
+                    name: "Iron Dollar".to_string(),
+                    symbol: "IRD".to_string(),
+                    decimals: 2,
+                }),
+            },
+            support::Extrinsic {
+                caller: aditya.clone(),
+                call: RuntimeCall::assets(assets::Call::mint {
+                    id: 1,
+                    beneficiary: aditya.clone(),
+                    amount: 1_000,
                 }),
             },
             support::Extrinsic {
-                caller: rajkumar,
-                call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-                    claim: "UNISWAP V3 DOCS",
+                caller: aditya.clone(),
+                call: RuntimeCall::assets(assets::Call::transfer {
+                    id: 1,
+                    to: dayitva.clone(),
+                    amount: 250,
                 }),
             },
         ],
     };
 
-    runtime
-        .execute_block(block_1)
-        .expect("Block execution failed");
-    runtime
-        .execute_block(block_2)
-        .expect("Block execution failed");
+    let block_4 = types::Block {
+        header: support::Header { block_number: 4, timestamp: now() },
+        extrinsics: vec![support::Extrinsic {
+            caller: dayitva,
+            call: RuntimeCall::utility(utility::Call::batch_all {
+                calls: vec![
+                    RuntimeCall::balances(balances::Call::transfer {
+                        to: aditya.clone(),
+                        amount: 5,
+                    }),
+                    RuntimeCall::assets(assets::Call::transfer {
+                        id: 1,
+                        to: aditya,
+                        amount: 50,
+                    }),
+                ],
+            }),
+        }],
+    };
+
+    for block in [block_1, block_2, block_3, block_4] {
+        let extrinsics = block.extrinsics.clone();
+        let receipt = runtime.execute_block(block).expect("Block execution failed");
+        for extrinsic in receipt.failed() {
+            eprintln!(
+                "Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tCall: {}\n\tError: {:?}",
+                receipt.block_number,
+                extrinsic.index,
+                extrinsics[extrinsic.index as usize].call,
+                extrinsic.result
+            );
+        }
+    }
 
     println!("{:?}", runtime);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::support::{self, Dispatch, ExecutionMode, DEFAULT_WEIGHT};
+    use crate::{balances, proof_of_existence, system, types, utility, Runtime, RuntimeCall, RuntimeEvent};
+
+    fn transfer(to: &str, amount: types::Balance) -> RuntimeCall {
+        RuntimeCall::balances(balances::Call::transfer { to: to.to_string(), amount })
+    }
+
+    fn extrinsic(caller: &str, call: RuntimeCall) -> types::Extrinsic {
+        support::Extrinsic { caller: caller.to_string(), call }
+    }
+
+    #[test]
+    fn execute_block_returns_receipt() {
+        let mut runtime = Runtime::new();
+        runtime.balances.set_balance(&"alice".to_string(), 100);
+        let claim = proof_of_existence::Pallet::<Runtime>::hash_content(b"Receipt");
+
+        let block = types::Block {
+            header: support::Header { block_number: 1, timestamp: None },
+            extrinsics: vec![
+                extrinsic("alice", transfer("bob", 30)),
+                extrinsic("bob", transfer("alice", 31)),
+                extrinsic(
+                    "alice",
+                    RuntimeCall::utility(utility::Call::batch {
+                        calls: vec![RuntimeCall::proof_of_existence(
+                            proof_of_existence::Call::create_claim { claim, expires_in: Some(0) },
+                        )],
+                    }),
+                ),
+            ],
+        };
+        let receipt = runtime.execute_block(block).expect("block number is valid");
+
+        assert_eq!(receipt.block_number, 1);
+        assert_eq!(receipt.extrinsics.len(), 3);
+
+        let first = &receipt.extrinsics[0];
+        assert_eq!((first.index, first.caller.as_str(), first.result), (0, "alice", Ok(())));
+        assert_eq!(
+            first.events,
+            vec![RuntimeEvent::balances(balances::Event::Transfer {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 30,
+            })]
+        );
+        assert_eq!(first.weight, DEFAULT_WEIGHT);
+
+        let second = &receipt.extrinsics[1];
+        assert_eq!(second.result, Err("Insufficient balance"));
+        assert!(second.events.is_empty());
+        assert_eq!(receipt.failed().map(|extrinsic| extrinsic.index).collect::<Vec<_>>(), vec![1]);
+
+        let third = &receipt.extrinsics[2];
+        assert_eq!(third.weight, 2 * DEFAULT_WEIGHT, "a batch weighs its calls on top of itself");
+        assert_eq!(third.events.last(), Some(&RuntimeEvent::utility(utility::Event::BatchCompleted)));
+        assert_eq!(receipt.total_weight(), 4 * DEFAULT_WEIGHT);
+
+        let mut heavy = receipt.clone();
+        heavy.extrinsics[0].weight = support::Weight::MAX;
+        assert_eq!(heavy.total_weight(), support::Weight::MAX);
+
+        // The claim expires at the end of the block.
+        assert_eq!(
+            receipt.finalize_events.last(),
+            Some(&RuntimeEvent::proof_of_existence(proof_of_existence::Event::ClaimExpired {
+                owner: "alice".to_string(),
+                claim,
+            }))
+        );
+    }
+
+    #[test]
+    fn claims_record_the_block_timestamp() {
+        let mut runtime = Runtime::new();
+        runtime.balances.set_balance(&"alice".to_string(), 100);
+        let claim = proof_of_existence::Pallet::<Runtime>::hash_content(b"Timestamp");
+
+        let block = types::Block {
+            header: support::Header { block_number: 1, timestamp: Some(1_700_000_000) },
+            extrinsics: vec![super::create_claim_extrinsic("alice".to_string(), claim)],
+        };
+        runtime.execute_block(block).expect("block number is valid");
+        assert_eq!(runtime.system.now(), Some(1_700_000_000));
+        let info = runtime.proof_of_existence.claim_info(&claim).expect("claim should exist");
+        assert_eq!(info.timestamp, Some(1_700_000_000));
+
+        let block = types::Block {
+            header: support::Header { block_number: 2, timestamp: None },
+            extrinsics: vec![],
+        };
+        runtime.execute_block(block).expect("block number is valid");
+        assert_eq!(runtime.system.now(), None);
+    }
+
+    #[test]
+    fn strict_mode_rolls_back_failing_block() {
+        let mut runtime = Runtime::new();
+        runtime.system.set_execution_mode(ExecutionMode::Strict);
+        runtime.balances.set_balance(&"alice".to_string(), 100);
+        let before = runtime.clone();
+
+        let block = types::Block {
+            header: support::Header { block_number: 1, timestamp: None },
+            extrinsics: vec![
+                extrinsic("alice", transfer("bob", 30)),
+                extrinsic("bob", transfer("alice", 31)),
+            ],
+        };
+        assert_eq!(runtime.execute_block(block), Err("Insufficient balance"));
+        assert_eq!(runtime, before, "the block is rolled back");
+
+        let block = types::Block {
+            header: support::Header { block_number: 1, timestamp: None },
+            extrinsics: vec![
+                extrinsic("alice", transfer("bob", 30)),
+                extrinsic("bob", transfer("alice", 30)),
+            ],
+        };
+        let receipt = runtime.execute_block(block).expect("all extrinsics succeed");
+        assert_eq!(receipt.failed().count(), 0);
+        assert_eq!(runtime.system.block_number(), 1);
+        assert_eq!(runtime.balances.balance(&"alice".to_string()), 100);
+
+        // The events of the previous block are kept when the next one is rejected.
+        let before = runtime.clone();
+        let block = types::Block {
+            header: support::Header { block_number: 2, timestamp: None },
+            extrinsics: vec![extrinsic("bob", transfer("alice", 1))],
+        };
+        assert_eq!(runtime.execute_block(block), Err("Insufficient balance"));
+        assert_eq!(runtime, before, "the block is rolled back");
+        assert_eq!(runtime.system.events().len(), 2);
+    }
+
+    #[test]
+    fn dry_run_leaves_state_untouched() {
+        let mut runtime = Runtime::new();
+        runtime.balances.set_balance(&"alice".to_string(), 100);
+        let before = runtime.clone();
+
+        let outcome = runtime.dry_run(extrinsic("alice", transfer("bob", 101)));
+        assert_eq!(outcome.result, Err("Insufficient balance"));
+        assert!(outcome.events.is_empty());
+
+        let outcome = runtime.dry_run(extrinsic("alice", transfer("bob", 30)));
+        assert_eq!(outcome.result, Ok(()));
+        assert_eq!(
+            outcome.events,
+            vec![RuntimeEvent::balances(balances::Event::Transfer {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 30,
+            })]
+        );
+        assert_eq!(outcome.weight, DEFAULT_WEIGHT);
+        assert_eq!(runtime, before);
+    }
+
+    #[test]
+    fn runtime_metadata() {
+        let metadata = Runtime::metadata();
+        let names = metadata.pallets.iter().map(|pallet| pallet.name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["system", "balances", "assets", "proof_of_existence", "utility", "stable"]
+        );
+
+        let balances = &metadata.pallets[1];
+        assert_eq!(balances.index, 1);
+        let transfer = &balances.calls[0];
+        assert_eq!((transfer.name, transfer.index), ("transfer", 0));
+        assert_eq!(transfer.docs[0], "Transfer `amount` from one account to another.");
+        assert_eq!(transfer.args[0].name, "to");
+        assert_eq!(transfer.args[0].ty, "T::AccountId");
+        assert_eq!(transfer.args[0].type_name, "alloc::string::String");
+        assert!(balances.errors.contains(&"Insufficient balance"));
+        assert!(balances.events.iter().any(|event| event.name == "Transfer"));
+        // Every declared error is listed, even when only returned through `Currency`.
+        assert!(balances.errors.contains(&"Insufficient reserved balance"));
+        assert_eq!(
+            metadata.pallets[0].errors,
+            vec!["Caller is not root", "Unknown storage key", "Invalid storage key", "Invalid storage value"]
+        );
+
+        let poe = &metadata.pallets[3];
+        assert!(poe.errors.contains(&"Caller is not the owner of the claim"));
+        assert!(poe.calls.iter().all(|call| !call.name.starts_with("ensure")));
+
+        let batch = &metadata.pallets[4].calls[0];
+        assert_eq!(batch.args[0].ty, "Vec<T::RuntimeCall>");
+        assert!(metadata.to_json().starts_with(r#"{"pallets":[{"name":"system","index":0,"#));
+    }
+
+    #[test]
+    fn call_indices() {
+        assert_eq!(transfer("bob", 1).call_index(), (1, 0));
+        let batch = RuntimeCall::utility(utility::Call::force_batch { calls: vec![] });
+        assert_eq!(batch.call_index(), (4, 2));
+
+        let metadata = Runtime::metadata();
+        let poe = &metadata.pallets[3];
+        let revoke = poe.calls.iter().find(|call| call.name == "revoke_claim").unwrap();
+        assert_eq!(revoke.index, 6);
+    }
+
+    #[test]
+    fn calls_are_comparable_and_displayable() {
+        let claim = proof_of_existence::Pallet::<Runtime>::hash_content(b"Display");
+        let batch = RuntimeCall::utility(utility::Call::batch_all {
+            calls: vec![
+                transfer("dayitva", 30),
+                RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+                    claim,
+                    expires_in: None,
+                }),
+            ],
+        });
+
+        let block = types::Block {
+            header: support::Header { block_number: 1, timestamp: None },
+            extrinsics: vec![extrinsic("alice", batch.clone())],
+        };
+        assert_eq!(block.clone(), block);
+        assert_ne!(batch, transfer("dayitva", 30));
+        assert_ne!(transfer("dayitva", 31), transfer("dayitva", 30));
+
+        assert_eq!(transfer("dayitva", 30).to_string(), "balances.transfer(to: dayitva, amount: 30)");
+        assert_eq!(
+            batch.to_string(),
+            format!(
+                "utility.batch_all(calls: [balances.transfer(to: dayitva, amount: 30), \
+                 proof_of_existence.create_claim(claim: {}, expires_in: None)])",
+                claim
+            )
+        );
+        assert_eq!(
+            format!("{:?}", transfer("dayitva", 30)),
+            r#"balances(transfer { to: "dayitva", amount: 30 })"#
+        );
+    }
+
+    #[test]
+    fn parse_calls() {
+        let call: RuntimeCall = r#"balances.transfer(amount=10, to="bob")"#.parse().unwrap();
+        assert_eq!(call, transfer("bob", 10));
+
+        let claim = proof_of_existence::Pallet::<Runtime>::hash_content(b"Parse");
+        let batch = RuntimeCall::utility(utility::Call::force_batch {
+            calls: vec![
+                transfer("dayitva", 30),
+                RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+                    claim,
+                    expires_in: Some(5),
+                }),
+            ],
+        });
+        assert_eq!(batch.to_string().parse(), Ok(batch));
+
+        let odd = transfer(r#"a b, "c") \d"#, 5);
+        assert_eq!(odd.to_string(), r#"balances.transfer(to: "a b, \"c\") \\d", amount: 5)"#);
+        assert_eq!(odd.to_string().parse(), Ok(odd));
+        assert_eq!(transfer("", 5).to_string().parse(), Ok(transfer("", 5)));
+
+        let error = |s: &str| s.parse::<RuntimeCall>().unwrap_err();
+        assert_eq!(error("bank.transfer(to=bob, amount=10)"), "unknown pallet `bank`");
+        assert_eq!(error("balances.send(to=bob, amount=10)"), "unknown call `send`");
+        assert_eq!(error("balances.transfer(to=bob)"), "missing argument `amount` of `transfer`");
+        assert_eq!(
+            error("balances.transfer(to=bob, amount=ten)"),
+            "invalid argument `amount` of `transfer`: expected u128, found `ten`"
+        );
+        assert_eq!(
+            error("balances.transfer(to=bob, amount=10, memo=hi)"),
+            "unknown argument `memo` of `transfer`"
+        );
+    }
+
+    #[test]
+    fn balances_instances_are_independent() {
+        let mut runtime = Runtime::new();
+        runtime.balances.set_balance(&"alice".to_string(), 100);
+        runtime.stable.set_balance(&"alice".to_string(), 50);
+
+        let call: RuntimeCall = "stable.transfer(to=bob, amount=20)".parse().unwrap();
+        assert_eq!(call.call_index(), (5, 0));
+        assert_eq!(runtime.dispatch("alice".to_string(), call), Ok(()));
+
+        assert_eq!(runtime.stable.balance(&"bob".to_string()), 20);
+        assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+        assert_eq!(runtime.balances.balance(&"alice".to_string()), 100);
+        assert_eq!(
+            runtime.system.events(),
+            &[RuntimeEvent::stable(balances::Event::Transfer {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 20u64,
+            })]
+        );
+
+        let metadata = Runtime::metadata();
+        assert_eq!(metadata.pallets[5].name, "stable");
+        let stable_amount = &metadata.pallets[5].calls[0].args[1];
+        assert_eq!((stable_amount.ty, stable_amount.type_name), ("T::Balance", "u64"));
+    }
+
+    #[test]
+    fn system_calls() {
+        let mut runtime = Runtime::new();
+        let remark = RuntimeCall::system(system::Call::remark_with_event { remark: b"hi".to_vec() });
+        assert_eq!(remark.call_index(), (0, 1));
+        assert_eq!(remark.to_string(), "system.remark_with_event(remark: [104, 105])");
+        assert_eq!(runtime.dispatch("alice".to_string(), remark), Ok(()));
+        assert_eq!(
+            runtime.system.events(),
+            &[RuntimeEvent::system(system::Event::Remarked {
+                sender: "alice".to_string(),
+                hash: proof_of_existence::Pallet::<Runtime>::hash_content(b"hi"),
+            })]
+        );
+    }
+
+    #[test]
+    fn root_storage_calls() {
+        use support::codec::Encode;
+        use support::storage::prefix_key;
+
+        let mut runtime = Runtime::new();
+        let mut balance = prefix_key("ironledger_rust_state_machine::balances::balances");
+        "bob".encode_to(&mut balance);
+        let mut nonce = prefix_key("ironledger_rust_state_machine::system::nonce");
+        "bob".encode_to(&mut nonce);
+        let items = vec![(balance.clone(), 100u128.encode()), (nonce.clone(), 7u32.encode())];
+
+        let set = RuntimeCall::system(system::Call::set_storage { items: items.clone() });
+        assert_eq!(runtime.dispatch("bob".to_string(), set.clone()), Err("Caller is not root"));
+        assert_eq!(runtime.dispatch(types::ROOT.to_string(), set), Ok(()));
+        assert_eq!(runtime.balances.balance(&"bob".to_string()), 100);
+        assert_eq!(runtime.system.get_nonce(&"bob".to_string()), 7);
+
+        // Nothing is set when one of the values is invalid.
+        let invalid = vec![(balance.clone(), 5u128.encode()), (nonce.clone(), vec![1])];
+        let set = RuntimeCall::system(system::Call::set_storage { items: invalid });
+        assert_eq!(runtime.dispatch(types::ROOT.to_string(), set), Err("Invalid storage value"));
+        assert_eq!(runtime.balances.balance(&"bob".to_string()), 100);
+
+        let kill = RuntimeCall::system(system::Call::kill_storage { keys: vec![balance, nonce] });
+        assert_eq!(runtime.dispatch(types::ROOT.to_string(), kill), Ok(()));
+        assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+        assert_eq!(runtime.system.get_nonce(&"bob".to_string()), 0);
+
+        let unknown = RuntimeCall::system(system::Call::kill_storage { keys: vec![vec![1]] });
+        assert_eq!(runtime.dispatch(types::ROOT.to_string(), unknown), Err("Unknown storage key"));
+    }
+
+    #[test]
+    fn raw_storage_routes_to_pallets() {
+        use support::codec::Encode;
+        use support::storage::{prefix_key, RawStorage, RawStorageError};
+
+        let mut runtime = Runtime::new();
+        let mut key = prefix_key("ironledger_rust_state_machine::balances::balances");
+        "alice".encode_to(&mut key);
+        assert!(runtime.owns_key(&key));
+        assert_eq!(runtime.set_raw(&key, &100u128.encode()), Ok(()));
+        assert_eq!(runtime.balances.balance(&"alice".to_string()), 100);
+        assert_eq!(runtime.get_raw(&key), Some(100u128.encode()));
+        assert_eq!(runtime.set_raw(&key, &[1]), Err(RawStorageError::InvalidValue));
+
+        let unknown = prefix_key("ironledger_rust_state_machine::balances::unknown");
+        assert!(!runtime.owns_key(&unknown));
+        assert_eq!(runtime.kill_raw(&unknown), Err(RawStorageError::UnknownKey));
+
+        assert_eq!(runtime.kill_raw(&key), Ok(()));
+        assert_eq!(runtime.balances.balance(&"alice".to_string()), 0);
+    }
+
+    #[test]
+    fn execute_block_rejects_wrong_block_number() {
+        let mut runtime = Runtime::new();
+        runtime.system.set_execution_mode(ExecutionMode::Strict);
+        let before = runtime.clone();
+
+        let block = types::Block {
+            header: support::Header { block_number: 2, timestamp: Some(1_700_000_000) },
+            extrinsics: vec![],
+        };
+        assert_eq!(runtime.execute_block(block), Err("block number does not match what is expected"));
+        assert_eq!(runtime, before, "a rejected block does not change the state");
+    }
+}
@@ -1,110 +1,1399 @@
-use crate::support::DispatchResult;
+use crate::support::{
+	codec::{Decode, Encode},
+	storage::{with_transaction, OptionQuery, StorageMap, Transactional},
+	Currency, DispatchResult, Hashing, HasPallet, Hooks, Moment, TakeEvents, Weight, DEFAULT_WEIGHT,
+};
+use crate::system;
 use core::fmt::Debug;
-use std::collections::BTreeMap;
+use num::traits::{CheckedAdd, One};
+use std::collections::BTreeSet;
+use std::path::Path;
 
 pub trait Config: crate::system::Config {
 	/// The type which represents the content that can be claimed using this pallet.
-	/// Could be the content directly as bytes, or better yet the hash of that content.
-	/// We leave that decision to the runtime developer.
-	type Content: Debug + Ord;
+	/// This is the hash of the content, so claims have a fixed size no matter how large the
+	/// document is, and the document itself never ends up in state.
+	type Content: Debug + Ord + Clone;
+	/// The hashing algorithm used to turn a document into its `Content`.
+	type Hashing: Hashing<Output = Self::Content>;
+	/// The currency used by this pallet to charge its users.
+	type Currency: Currency<Self::AccountId>;
+
+	/// The amount reserved from the owner of a claim for as long as the claim exists.
+	const CLAIM_DEPOSIT: BalanceOf<Self>;
+	/// The maximum number of claims a single account can own, if any.
+	const MAX_CLAIMS_PER_ACCOUNT: Option<u32> = None;
+	/// The maximum number of claims which can be created or revoked in a single batch.
+	const MAX_BATCH_SIZE: u32 = 100;
+	/// The maximum number of expired claims removed at the end of a block. Any remaining expired
+	/// claims are removed in the following blocks.
+	const MAX_EXPIRIES_PER_BLOCK: u32 = 100;
+}
+
+/// The balance type of the `Currency` configured for the pallet.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+/// Everything we know about a claim, which together proves who claimed the content and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimInfo<AccountId, BlockNumber, Balance> {
+	/// The owner of the claim.
+	pub owner: AccountId,
+	/// The block in which the claim was created.
+	pub block_number: BlockNumber,
+	/// The time at which the claim was created, if the block it was created in has a timestamp.
+	pub timestamp: Option<Moment>,
+	/// The amount reserved from the owner for as long as the claim exists.
+	pub deposit: Balance,
+	/// The block at the end of which the claim is removed, if it expires.
+	pub expires_at: Option<BlockNumber>,
+}
+
+impl<AccountId: Encode, BlockNumber: Encode, Balance: Encode> Encode
+	for ClaimInfo<AccountId, BlockNumber, Balance>
+{
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.owner.encode_to(dest);
+		self.block_number.encode_to(dest);
+		self.timestamp.encode_to(dest);
+		self.deposit.encode_to(dest);
+		self.expires_at.encode_to(dest);
+	}
+}
+
+impl<AccountId: Decode, BlockNumber: Decode, Balance: Decode> Decode
+	for ClaimInfo<AccountId, BlockNumber, Balance>
+{
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some(Self {
+			owner: Decode::decode(input)?,
+			block_number: Decode::decode(input)?,
+			timestamp: Decode::decode(input)?,
+			deposit: Decode::decode(input)?,
+			expires_at: Decode::decode(input)?,
+		})
+	}
+}
+
+/// The `ClaimInfo` stored by the pallet for a runtime `T`.
+pub type ClaimInfoOf<T> = ClaimInfo<
+	<T as system::Config>::AccountId,
+	<T as system::Config>::BlockNumber,
+	BalanceOf<T>,
+>;
+
+/// A claim proposed by its creator, which becomes final once enough of its signers attest to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal<AccountId, Balance> {
+	/// The account which proposed the claim, and owns it once it is final.
+	pub creator: AccountId,
+	/// The accounts which are allowed to attest to the claim.
+	pub signers: BTreeSet<AccountId>,
+	/// The number of attestations needed for the claim to become final.
+	pub threshold: u32,
+	/// The amount reserved from the creator, which is kept as the deposit of the final claim.
+	pub deposit: Balance,
+}
+
+impl<AccountId: Encode, Balance: Encode> Encode for Proposal<AccountId, Balance> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.creator.encode_to(dest);
+		self.signers.encode_to(dest);
+		self.threshold.encode_to(dest);
+		self.deposit.encode_to(dest);
+	}
+}
+
+impl<AccountId: Decode + Ord, Balance: Decode> Decode for Proposal<AccountId, Balance> {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some(Self {
+			creator: Decode::decode(input)?,
+			signers: Decode::decode(input)?,
+			threshold: Decode::decode(input)?,
+			deposit: Decode::decode(input)?,
+		})
+	}
+}
+
+/// The `Proposal` stored by the pallet for a runtime `T`.
+pub type ProposalOf<T> = Proposal<<T as system::Config>::AccountId, BalanceOf<T>>;
+
+/// The events emitted by the proof of existence pallet.
+#[derive(Debug, Clone, PartialEq, macros::EventMetadata)]
+pub enum Event<T: Config> {
+	/// `owner` claimed `claim`, either directly or once a co-signed claim became final.
+	ClaimCreated { owner: T::AccountId, claim: T::Content },
+	/// `owner` revoked `claim`.
+	ClaimRevoked { owner: T::AccountId, claim: T::Content },
+	/// `claim`, owned by `owner`, expired and was removed.
+	ClaimExpired { owner: T::AccountId, claim: T::Content },
+	/// The expiry of `claim` was pushed back to `expires_at`.
+	ClaimRenewed { claim: T::Content, expires_at: T::BlockNumber },
+	/// `claim` could not be removed when it expired because of `error`, and is tried again at the
+	/// end of block `expires_at`.
+	ExpiryDeferred { claim: T::Content, expires_at: T::BlockNumber, error: &'static str },
+	/// `claim` was handed over from `from` to `to`.
+	ClaimTransferred { from: T::AccountId, to: T::AccountId, claim: T::Content },
+	/// `claim` was offered to `to`.
+	ClaimOffered { claim: T::Content, to: T::AccountId },
+	/// The pending offer of `claim` was withdrawn.
+	OfferCancelled { claim: T::Content },
+	/// `creator` proposed `claim` as a co-signed claim.
+	ClaimProposed { creator: T::AccountId, claim: T::Content },
+	/// `signer` attested to the co-signed `claim`.
+	ClaimAttested { signer: T::AccountId, claim: T::Content },
+	/// The co-signed `claim` was withdrawn before becoming final.
+	ProposalCancelled { claim: T::Content },
+}
+
+/// The errors returned by the proof of existence pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+pub enum Error {
+	/// The content has already been claimed.
+	#[error("Claim already exists")]
+	ClaimAlreadyExists,
+	/// The content has not been claimed.
+	#[error("Claim does not exist")]
+	ClaimDoesNotExist,
+	/// Only the owner of the claim can do this.
+	#[error("Caller is not the owner of the claim")]
+	NotClaimOwner,
+	/// The new owner of the claim is its current owner.
+	#[error("Cannot transfer a claim to its owner")]
+	TransferToOwner,
+	/// The account already owns `MAX_CLAIMS_PER_ACCOUNT` claims.
+	#[error("Too many claims for account")]
+	TooManyClaims,
+	/// The batch has more than `MAX_BATCH_SIZE` claims.
+	#[error("Too many claims in batch")]
+	BatchTooLarge,
+	/// The same content appears twice in a batch.
+	#[error("Duplicate claim in batch")]
+	DuplicateClaimInBatch,
+	/// The claim was created without an expiry.
+	#[error("Claim does not expire")]
+	ClaimDoesNotExpire,
+	/// The block number of the expiry would overflow.
+	#[error("Overflow in expiry")]
+	ExpiryOverflow,
+	/// The claim has no pending offer.
+	#[error("Claim has not been offered")]
+	NotOffered,
+	/// The claim is not offered to the caller.
+	#[error("Claim has not been offered to the caller")]
+	NotOfferedToCaller,
+	/// The content is waiting for attestations as a co-signed claim.
+	#[error("Claim already proposed")]
+	AlreadyProposed,
+	/// The content is not a proposed co-signed claim.
+	#[error("Claim has not been proposed")]
+	NotProposed,
+	/// The threshold is zero, or larger than the number of signers.
+	#[error("Invalid threshold")]
+	InvalidThreshold,
+	/// The caller is not one of the signers of the co-signed claim.
+	#[error("Caller is not a signer of the claim")]
+	NotSigner,
+	/// Only the creator of the co-signed claim can do this.
+	#[error("Caller is not the creator of the claim")]
+	NotCreator,
+	/// The caller already attested to the co-signed claim.
+	#[error("Caller already attested")]
+	AlreadyAttested,
 }
 
 /// This is the Proof of Existence Module.
 /// It is a simple module that allows accounts to claim existence of some data.
-#[derive(Debug)]
+#[macros::storage]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pallet<T: Config> {
-	/// A simple storage map from content to the owner of that content.
+	/// A simple storage map from content to the owner of that content, and when it was claimed.
 	/// Accounts can make multiple different claims, but each claim can only have one owner.
-	claims: BTreeMap<T::Content, T::AccountId>,
+	claims: StorageMap<T::Content, ClaimInfoOf<T>, OptionQuery>,
+	/// Pending offers of a claim by its owner to another account, which has yet to accept it.
+	offers: StorageMap<T::Content, T::AccountId, OptionQuery>,
+	/// A reverse index from an owner to all of the claims it owns, kept in sync with `claims`.
+	claims_by_owner: StorageMap<T::AccountId, BTreeSet<T::Content>>,
+	/// The claims which expire at the end of each block.
+	expiries: StorageMap<T::BlockNumber, BTreeSet<T::Content>>,
+	/// Co-signed claims which have been proposed, but not yet attested by enough signers.
+	proposals: StorageMap<T::Content, ProposalOf<T>, OptionQuery>,
+	/// The number of co-signed claims proposed by each account which are not final yet. They count
+	/// towards the claims of the account, so that they can always become final.
+	pending_proposals: StorageMap<T::AccountId, u32>,
+	/// The signers which attested to a co-signed claim, whether it is final or not.
+	attestations: StorageMap<T::Content, BTreeSet<T::AccountId>>,
+	/// The events emitted since the runtime last collected them.
+	events: Vec<Event<T>>,
 }
 
-#[macros::call]
-impl<T: Config> Pallet<T> {
+#[macros::call(explicit_call_index, error = Error)]
+impl<T: Config> Pallet<T>
+where
+	T: HasPallet<system::Pallet<T>> + HasPallet<T::Currency> + HasPallet<Pallet<T>> + Transactional,
+{
 	/// Create a new claim on behalf of the `caller`.
-	/// This function will return an error if someone already has claimed that content.
-	pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+	/// The current block number and time are recorded with the claim, and `CLAIM_DEPOSIT` is
+	/// reserved from the caller until the claim is revoked. If `expires_in` is given, the claim
+	/// is removed that many blocks after the current one, unless it is renewed.
+	/// This function will return an error if someone already has claimed that content, if the
+	/// caller already owns the maximum number of claims, or cannot afford the deposit.
+	#[call_index(0)]
+	pub fn create_claim(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claim: T::Content,
+		expires_in: Option<T::BlockNumber>,
+	) -> DispatchResult {
 		// Check if the claim already exists
-		match self.get_claim(&claim) {
-			Some(_) => Err("Claim already exists"),
-			None => {
-				// If it does not exist, insert the new claim
-				self.claims.insert(claim, caller);
-				Ok(())
+		let poe: &Self = runtime.pallet();
+		if poe.get_claim(&claim).is_some() {
+			return Err(Error::ClaimAlreadyExists.into());
+		}
+		poe.ensure_not_proposed(&claim)?;
+		poe.ensure_can_own(&caller)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let (block_number, timestamp) = (system.block_number(), system.now());
+		let expires_at = match expires_in {
+			Some(blocks) => Some(block_number.checked_add(&blocks).ok_or(Error::ExpiryOverflow)?),
+			None => None,
+		};
+
+		let deposit = T::CLAIM_DEPOSIT;
+		let currency: &mut T::Currency = runtime.pallet_mut();
+		currency.reserve(&caller, deposit)?;
+
+		// If it does not exist, insert the new claim
+		let poe: &mut Self = runtime.pallet_mut();
+		poe.deposit_event(Event::ClaimCreated { owner: caller.clone(), claim: claim.clone() });
+		let info = ClaimInfo { owner: caller, block_number, timestamp, deposit, expires_at };
+		poe.insert_claim(claim, info);
+		Ok(())
+	}
+
+	/// Create many claims on behalf of the `caller`, like `create_claim` without an expiry.
+	/// Either all the claims are created, or none of them are.
+	/// This function will return an error if there are more than `MAX_BATCH_SIZE` claims, or if
+	/// any of them could not be created.
+	#[call_index(1)]
+	#[weight(DEFAULT_WEIGHT.saturating_mul(claims.len() as Weight))]
+	pub fn create_claims(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claims: Vec<T::Content>,
+	) -> DispatchResult {
+		Self::ensure_batch_size(&claims)?;
+
+		with_transaction(runtime, |runtime| {
+			claims
+				.into_iter()
+				.try_for_each(|claim| Self::create_claim(runtime, caller.clone(), claim, None))
+		})
+	}
+
+	/// Revoke many claims owned by the `caller`, like `revoke_claim`.
+	/// Either all the claims are revoked, or none of them are.
+	/// This function will return an error if there are more than `MAX_BATCH_SIZE` claims, if the
+	/// same claim is given twice, or if any of them does not exist or is not owned by the caller.
+	#[call_index(2)]
+	#[weight(DEFAULT_WEIGHT.saturating_mul(claims.len() as Weight))]
+	pub fn revoke_claims(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claims: Vec<T::Content>,
+	) -> DispatchResult {
+		Self::ensure_batch_size(&claims)?;
+
+		let poe: &Self = runtime.pallet();
+		let mut unique = BTreeSet::new();
+		for claim in &claims {
+			if !unique.insert(claim) {
+				return Err(Error::DuplicateClaimInBatch.into());
+			}
+			poe.ensure_owner(&caller, claim)?;
+		}
+
+		// Returning a deposit can still fail, after some of the claims were removed.
+		with_transaction(runtime, |runtime| {
+			for claim in claims {
+				Self::remove_claim(runtime, &claim)?;
+				let poe: &mut Self = runtime.pallet_mut();
+				poe.deposit_event(Event::ClaimRevoked { owner: caller.clone(), claim });
 			}
+			Ok(())
+		})
+	}
+
+	/// Propose a co-signed claim on behalf of the `caller`, which becomes final once `threshold`
+	/// of the `signers` have attested to it with `attest`. When no threshold is given, all the
+	/// signers have to attest. `CLAIM_DEPOSIT` is reserved from the caller right away.
+	/// This function will return an error if the content is already claimed or proposed, if the
+	/// threshold can not be reached, or if the caller cannot afford the deposit.
+	#[call_index(3)]
+	pub fn propose_claim(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claim: T::Content,
+		signers: Vec<T::AccountId>,
+		threshold: Option<u32>,
+	) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		if poe.get_claim(&claim).is_some() {
+			return Err(Error::ClaimAlreadyExists.into());
+		}
+		poe.ensure_not_proposed(&claim)?;
+		poe.ensure_can_own(&caller)?;
+
+		let signers = signers.into_iter().collect::<BTreeSet<_>>();
+		let threshold = threshold.unwrap_or(signers.len() as u32);
+		if threshold == 0 || threshold as usize > signers.len() {
+			return Err(Error::InvalidThreshold.into());
+		}
+
+		let deposit = T::CLAIM_DEPOSIT;
+		let currency: &mut T::Currency = runtime.pallet_mut();
+		currency.reserve(&caller, deposit)?;
+
+		let poe: &mut Self = runtime.pallet_mut();
+		let proposal = Proposal { creator: caller.clone(), signers, threshold, deposit };
+		poe.proposals.insert(claim.clone(), proposal);
+		poe.pending_proposals.mutate(caller.clone(), |count| *count += 1);
+		poe.attestations.insert(claim.clone(), BTreeSet::new());
+		poe.deposit_event(Event::ClaimProposed { creator: caller, claim });
+		Ok(())
+	}
+
+	/// Attest to a proposed co-signed claim on behalf of the `caller`.
+	/// Once enough signers have attested, the claim becomes final and is owned by its creator.
+	/// This function will return an error if the claim is not proposed, if the caller is not one
+	/// of its signers, or if the caller already attested.
+	#[call_index(4)]
+	pub fn attest(runtime: &mut T, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		let proposal = poe.proposals.get(&claim).ok_or(Error::NotProposed)?;
+		if !proposal.signers.contains(&caller) {
+			return Err(Error::NotSigner.into());
 		}
+
+		let attestations = poe.attestations.get(&claim);
+		if attestations.contains(&caller) {
+			return Err(Error::AlreadyAttested.into());
+		}
+
+		if attestations.len() + 1 < proposal.threshold as usize {
+			let poe: &mut Self = runtime.pallet_mut();
+			poe.attestations.mutate(claim.clone(), |signers| signers.insert(caller.clone()));
+			poe.deposit_event(Event::ClaimAttested { signer: caller, claim });
+			return Ok(());
+		}
+
+		// This is the last attestation needed, so the claim becomes final. The creator kept room
+		// for it under `MAX_CLAIMS_PER_ACCOUNT` when proposing it.
+		let system: &system::Pallet<T> = runtime.pallet();
+		let info = ClaimInfo {
+			owner: proposal.creator,
+			block_number: system.block_number(),
+			timestamp: system.now(),
+			deposit: proposal.deposit,
+			expires_at: None,
+		};
+		let poe: &mut Self = runtime.pallet_mut();
+		poe.attestations.mutate(claim.clone(), |signers| signers.insert(caller.clone()));
+		poe.remove_proposal(&claim);
+		poe.deposit_event(Event::ClaimAttested { signer: caller, claim: claim.clone() });
+		poe.deposit_event(Event::ClaimCreated { owner: info.owner.clone(), claim: claim.clone() });
+		poe.insert_claim(claim, info);
+		Ok(())
 	}
 
+	/// Withdraw a co-signed claim which is not final yet, returning its deposit to the caller.
+	/// This function will return an error if the claim is not proposed, or if the caller is not
+	/// its creator.
+	#[call_index(5)]
+	pub fn cancel_proposal(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claim: T::Content,
+	) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		let proposal = poe.proposals.get(&claim).ok_or(Error::NotProposed)?;
+		if proposal.creator != caller {
+			return Err(Error::NotCreator.into());
+		}
+
+		let currency: &mut T::Currency = runtime.pallet_mut();
+		currency.unreserve(&caller, proposal.deposit)?;
 
-	/// Revoke an existing claim on some content.
+		let poe: &mut Self = runtime.pallet_mut();
+		poe.remove_proposal(&claim);
+		poe.attestations.remove(&claim);
+		poe.deposit_event(Event::ProposalCancelled { claim });
+		Ok(())
+	}
+
+
+	/// Revoke an existing claim on some content, returning its deposit to the caller.
 	/// This function should only succeed if the caller is the owner of an existing claim.
 	/// It will return an error if the claim does not exist, or if the caller is not the owner.
-	pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-		let claim_owner = self.get_claim(&claim).ok_or("Claim does not exist")?;
+	#[call_index(6)]
+	pub fn revoke_claim(runtime: &mut T, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		poe.ensure_owner(&caller, &claim)?;
+
+		Self::remove_claim(runtime, &claim)?;
+		let poe: &mut Self = runtime.pallet_mut();
+		poe.deposit_event(Event::ClaimRevoked { owner: caller, claim });
+		Ok(())
+	}
 
-		if claim_owner != &caller {
-			return Err("Caller is not the owner of the claim");
+	/// Push back the expiry of an existing claim by `extend_by` blocks.
+	/// This function should only succeed if the caller is the owner of an existing claim.
+	/// It will return an error if the claim does not exist, if the caller is not the owner, or if
+	/// the claim does not expire.
+	#[call_index(7)]
+	pub fn renew_claim(
+		&mut self,
+		caller: T::AccountId,
+		claim: T::Content,
+		extend_by: T::BlockNumber,
+	) -> DispatchResult {
+		self.ensure_owner(&caller, &claim)?;
+
+		let mut info = self.claims.get(&claim).ok_or(Error::ClaimDoesNotExist)?;
+		let expires_at = info.expires_at.ok_or(Error::ClaimDoesNotExpire)?;
+		let new_expires_at = expires_at.checked_add(&extend_by).ok_or(Error::ExpiryOverflow)?;
+		info.expires_at = Some(new_expires_at);
+		self.claims.insert(claim.clone(), info);
+
+		self.remove_from_expiries(&expires_at, &claim);
+		self.expiries.mutate(new_expires_at, |claims| claims.insert(claim.clone()));
+		self.deposit_event(Event::ClaimRenewed { claim, expires_at: new_expires_at });
+		Ok(())
+	}
+
+	/// Transfer an existing claim to `new_owner` in a single step.
+	/// The block number and time at which the claim was created are kept, while the deposit is
+	/// returned to the caller and reserved from `new_owner` instead.
+	/// It will return an error if the claim does not exist, if the caller is not the owner, if
+	/// `new_owner` is the caller, or if `new_owner` already owns the maximum number of claims or
+	/// cannot afford the deposit.
+	#[call_index(8)]
+	pub fn transfer_claim(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claim: T::Content,
+		new_owner: T::AccountId,
+	) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		poe.ensure_owner(&caller, &claim)?;
+		if new_owner == caller {
+			return Err(Error::TransferToOwner.into());
 		}
+		poe.ensure_can_own(&new_owner)?;
 
-		self.claims.remove(&claim);
+		Self::move_deposit(runtime, &claim, &new_owner)?;
+		let poe: &mut Self = runtime.pallet_mut();
+		poe.set_owner(&claim, new_owner);
 		Ok(())
 	}
+
+	/// Offer an existing claim to `new_owner`, who has to accept it with `accept_claim`.
+	/// A new offer replaces any previous offer for the same claim.
+	/// It will return an error if the claim does not exist, if the caller is not the owner, or if
+	/// `new_owner` is the caller.
+	#[call_index(9)]
+	pub fn offer_claim(
+		&mut self,
+		caller: T::AccountId,
+		claim: T::Content,
+		new_owner: T::AccountId,
+	) -> DispatchResult {
+		self.ensure_owner(&caller, &claim)?;
+		if new_owner == caller {
+			return Err(Error::TransferToOwner.into());
+		}
+
+		self.offers.insert(claim.clone(), new_owner.clone());
+		self.deposit_event(Event::ClaimOffered { claim, to: new_owner });
+		Ok(())
+	}
+
+	/// Withdraw a pending offer of a claim.
+	/// It will return an error if the claim does not exist, or if the caller is not the owner.
+	#[call_index(10)]
+	pub fn cancel_offer(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+		self.ensure_owner(&caller, &claim)?;
+
+		self.offers.remove(&claim).ok_or(Error::NotOffered)?;
+		self.deposit_event(Event::OfferCancelled { claim });
+		Ok(())
+	}
+
+	/// Accept a claim which was offered to the `caller`, who becomes its new owner.
+	/// The deposit of the claim is returned to the previous owner and reserved from the caller.
+	/// It will return an error if the claim was not offered to the caller, or if the caller
+	/// already owns the maximum number of claims or cannot afford the deposit.
+	#[call_index(11)]
+	pub fn accept_claim(runtime: &mut T, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		match poe.offer(&claim) {
+			Some(offered_to) if offered_to == &caller => {},
+			_ => return Err(Error::NotOfferedToCaller.into()),
+		}
+		poe.ensure_can_own(&caller)?;
+
+		Self::move_deposit(runtime, &claim, &caller)?;
+
+		let poe: &mut Self = runtime.pallet_mut();
+		poe.set_owner(&claim, caller);
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: HasPallet<T::Currency> + HasPallet<Pallet<T>> + Transactional,
+{
+	/// Remove an existing claim with everything associated to it, and return its deposit to the
+	/// owner.
+	fn remove_claim(runtime: &mut T, claim: &T::Content) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		let info = poe.claim_info(claim).ok_or(Error::ClaimDoesNotExist)?;
+		let (owner, deposit) = (info.owner.clone(), info.deposit);
+		let currency: &mut T::Currency = runtime.pallet_mut();
+		currency.unreserve(&owner, deposit)?;
+
+		let poe: &mut Self = runtime.pallet_mut();
+		let info = poe.claims.remove(claim).ok_or(Error::ClaimDoesNotExist)?;
+		poe.offers.remove(claim);
+		poe.attestations.remove(claim);
+		poe.remove_from_index(&info.owner, claim);
+		if let Some(expires_at) = info.expires_at {
+			poe.remove_from_expiries(&expires_at, claim);
+		}
+		Ok(())
+	}
+
+	/// Reserve the deposit of an existing claim from `new_owner`, and return it to the current
+	/// owner. Nothing is changed if `new_owner` cannot afford the deposit, or if it cannot be
+	/// returned to the current owner.
+	fn move_deposit(runtime: &mut T, claim: &T::Content, new_owner: &T::AccountId) -> DispatchResult {
+		let poe: &Self = runtime.pallet();
+		let info = poe.claim_info(claim).ok_or(Error::ClaimDoesNotExist)?;
+		let (owner, deposit) = (info.owner.clone(), info.deposit);
+
+		with_transaction(runtime, |runtime| {
+			let currency: &mut T::Currency = runtime.pallet_mut();
+			currency.reserve(new_owner, deposit)?;
+			currency.unreserve(&owner, deposit)
+		})
+	}
 }
 
 impl<T: Config> Pallet<T> {
-	/// Create a new instance of the Proof of Existence Module.
-	pub fn new() -> Self {
-		Self { claims: BTreeMap::new() }
+	/// Hash a document into the `Content` which can be claimed.
+	pub fn hash_content(data: &[u8]) -> T::Content {
+		T::Hashing::hash(data)
+	}
+
+	/// Read a local file and hash it into the `Content` which can be claimed.
+	pub fn hash_file(path: impl AsRef<Path>) -> std::io::Result<T::Content> {
+		Ok(Self::hash_content(&std::fs::read(path)?))
 	}
 
 	/// Get the owner (if any) of a claim.
 	pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
-		self.claims.get(claim)
+		self.claim_info(claim).map(|info| &info.owner)
+	}
+
+	/// Get the owner, block number and timestamp (if any) of a claim.
+	pub fn claim_info(&self, claim: &T::Content) -> Option<&ClaimInfoOf<T>> {
+		self.claims.get_ref(claim)
+	}
+
+	/// Get the account (if any) a claim has been offered to.
+	pub fn offer(&self, claim: &T::Content) -> Option<&T::AccountId> {
+		self.offers.get_ref(claim)
+	}
+
+	/// Get a page of the claims owned by `owner`, in the order of their content.
+	/// Pages are numbered from zero, and the last page may hold fewer than `page_size` claims.
+	pub fn claims_of(&self, owner: &T::AccountId, page: usize, page_size: usize) -> Vec<&T::Content> {
+		match self.claims_by_owner.get_ref(owner) {
+			Some(claims) => claims.iter().skip(page.saturating_mul(page_size)).take(page_size).collect(),
+			None => Vec::new(),
+		}
+	}
+
+	/// Get the number of claims owned by `owner`.
+	pub fn claim_count(&self, owner: &T::AccountId) -> usize {
+		self.claims_by_owner.get_ref(owner).map_or(0, |claims| claims.len())
+	}
+
+	/// Check that `who` can own one more claim without going over `MAX_CLAIMS_PER_ACCOUNT`,
+	/// counting the co-signed claims it proposed which are not final yet.
+	fn ensure_can_own(&self, who: &T::AccountId) -> DispatchResult {
+		let count = self.claim_count(who) + self.pending_proposals.get(who) as usize;
+		match T::MAX_CLAIMS_PER_ACCOUNT {
+			Some(max) if count >= max as usize => Err(Error::TooManyClaims.into()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Remove a co-signed claim which is no longer pending from the proposals of its creator.
+	fn remove_proposal(&mut self, claim: &T::Content) {
+		let Some(proposal) = self.proposals.remove(claim) else { return };
+		let count = self.pending_proposals.get(&proposal.creator).saturating_sub(1);
+		if count == 0 {
+			self.pending_proposals.remove(&proposal.creator);
+		} else {
+			self.pending_proposals.insert(proposal.creator, count);
+		}
+	}
+
+	/// Record in the reverse index that `owner` owns `claim`.
+	fn add_to_index(&mut self, owner: &T::AccountId, claim: &T::Content) {
+		self.claims_by_owner.mutate(owner.clone(), |claims| claims.insert(claim.clone()));
+	}
+
+	/// Remove `claim` from the claims of `owner` in the reverse index.
+	fn remove_from_index(&mut self, owner: &T::AccountId, claim: &T::Content) {
+		let is_empty = self.claims_by_owner.mutate(owner.clone(), |claims| {
+			claims.remove(claim);
+			claims.is_empty()
+		});
+		if is_empty {
+			self.claims_by_owner.remove(owner);
+		}
+	}
+
+	/// Get the claims (if any) which expire at the end of `block_number`.
+	pub fn expiring_at(&self, block_number: &T::BlockNumber) -> Option<&BTreeSet<T::Content>> {
+		self.expiries.get_ref(block_number)
+	}
+
+	/// Get the proposal (if any) of a co-signed claim which is not final yet.
+	pub fn proposal(&self, claim: &T::Content) -> Option<&ProposalOf<T>> {
+		self.proposals.get_ref(claim)
+	}
+
+	/// Get the signers (if any) which attested to a co-signed claim, whether it is final or not.
+	pub fn attestations(&self, claim: &T::Content) -> Option<&BTreeSet<T::AccountId>> {
+		self.attestations.get_ref(claim)
+	}
+
+	/// Store a new claim, and add it to the reverse index and expiry queue.
+	fn insert_claim(&mut self, claim: T::Content, info: ClaimInfoOf<T>) {
+		self.add_to_index(&info.owner, &claim);
+		if let Some(expires_at) = info.expires_at {
+			self.expiries.mutate(expires_at, |claims| claims.insert(claim.clone()));
+		}
+		self.claims.insert(claim, info);
+	}
+
+	/// Keep an expired `claim` which could not be removed, and schedule it to expire again at the
+	/// end of the block after `block_number`.
+	fn defer_expiry(
+		&mut self,
+		claim: &T::Content,
+		block_number: T::BlockNumber,
+		error: &'static str,
+	) {
+		let Some(mut info) = self.claims.get(claim) else { return };
+		let (Some(expires_at), Some(retry_at)) =
+			(info.expires_at, block_number.checked_add(&T::BlockNumber::one()))
+		else {
+			return;
+		};
+
+		info.expires_at = Some(retry_at);
+		self.claims.insert(claim.clone(), info);
+		self.remove_from_expiries(&expires_at, claim);
+		self.expiries.mutate(retry_at, |claims| claims.insert(claim.clone()));
+		self.deposit_event(Event::ExpiryDeferred { claim: claim.clone(), expires_at: retry_at, error });
+	}
+
+	/// Remove `claim` from the claims expiring at the end of `block_number`.
+	fn remove_from_expiries(&mut self, block_number: &T::BlockNumber, claim: &T::Content) {
+		let is_empty = self.expiries.mutate(*block_number, |claims| {
+			claims.remove(claim);
+			claims.is_empty()
+		});
+		if is_empty {
+			self.expiries.remove(block_number);
+		}
+	}
+
+	/// Check that a batch of claims is no larger than `MAX_BATCH_SIZE`.
+	fn ensure_batch_size(claims: &[T::Content]) -> DispatchResult {
+		if claims.len() > T::MAX_BATCH_SIZE as usize {
+			return Err(Error::BatchTooLarge.into());
+		}
+		Ok(())
+	}
+
+	/// Check that `claim` is not waiting for attestations as a co-signed claim.
+	fn ensure_not_proposed(&self, claim: &T::Content) -> DispatchResult {
+		if self.proposals.contains_key(claim) {
+			return Err(Error::AlreadyProposed.into());
+		}
+		Ok(())
+	}
+
+	/// Check that `claim` exists, and that `who` is its owner.
+	fn ensure_owner(&self, who: &T::AccountId, claim: &T::Content) -> DispatchResult {
+		let claim_owner = self.get_claim(claim).ok_or(Error::ClaimDoesNotExist)?;
+
+		if claim_owner != who {
+			return Err(Error::NotClaimOwner.into());
+		}
+
+		Ok(())
+	}
+
+	/// Hand an existing claim over to `new_owner`, clearing any pending offer.
+	fn set_owner(&mut self, claim: &T::Content, new_owner: T::AccountId) {
+		let Some(mut info) = self.claims.get(claim) else { return };
+		let old_owner = core::mem::replace(&mut info.owner, new_owner.clone());
+		self.claims.insert(claim.clone(), info);
+
+		self.remove_from_index(&old_owner, claim);
+		self.add_to_index(&new_owner, claim);
+		self.offers.remove(claim);
+		self.deposit_event(Event::ClaimTransferred {
+			from: old_owner,
+			to: new_owner,
+			claim: claim.clone(),
+		});
+	}
+
+	/// Emit an event, to be collected by the runtime.
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
 	}
 }
 
-// A public enum which describes the calls we want to expose to the dispatcher.
-// We should expect that the caller of each call will be provided by the dispatcher,
-// and not included as a parameter of the call.
+impl<T: Config> Hooks<T> for Pallet<T>
+where
+	T: HasPallet<system::Pallet<T>> + HasPallet<T::Currency> + HasPallet<Pallet<T>> + Transactional,
+{
+	/// Remove the claims which have expired, oldest first, up to `MAX_EXPIRIES_PER_BLOCK`.
+	fn on_finalize(runtime: &mut T) {
+		let system: &system::Pallet<T> = runtime.pallet();
+		let block_number = system.block_number();
 
-/// Implementation of the dispatch logic, mapping from `POECall` to the appropriate underlying
-/// function we want to execute.
+		let poe: &Self = runtime.pallet();
+		let expired = poe
+			.expiries
+			.iter()
+			.take_while(|(expires_at, _)| **expires_at <= block_number)
+			.flat_map(|(_, claims)| claims.iter().cloned())
+			.take(T::MAX_EXPIRIES_PER_BLOCK as usize)
+			.collect::<Vec<_>>();
 
+		for claim in expired {
+			let poe: &Self = runtime.pallet();
+			let Some(owner) = poe.get_claim(&claim).cloned() else { continue };
+			let result = Self::remove_claim(runtime, &claim);
+			let poe: &mut Self = runtime.pallet_mut();
+			match result {
+				Ok(()) => poe.deposit_event(Event::ClaimExpired { owner, claim }),
+				Err(error) => poe.defer_expiry(&claim, block_number, error),
+			}
+		}
+	}
+}
 
+impl<T: Config> TakeEvents for Pallet<T> {
+	type Event = Event<T>;
 
+	fn take_events(&mut self) -> Vec<Event<T>> {
+		core::mem::take(&mut self.events)
+	}
+}
 
 #[cfg(test)]
 mod test {
-	struct TestConfig;
+	use super::Event;
+	use crate::support::{storage::Transactional, BlakeTwo256, Currency, HasPallet, Hooks, TakeEvents, H256};
+	use crate::{balances, system};
+
+	type Poe = super::Pallet<TestConfig>;
+
+	#[derive(Debug, Clone, PartialEq)]
+	struct TestConfig {
+		system: system::Pallet<TestConfig>,
+		balances: balances::Pallet<TestConfig>,
+		poe: Poe,
+		/// The number of events of the pallet when each open transaction started.
+		event_marks: Vec<usize>,
+	}
 
 	impl super::Config for TestConfig {
-		type Content = &'static str;
+		type Content = H256;
+		type Hashing = BlakeTwo256;
+		type Currency = balances::Pallet<TestConfig>;
+		const CLAIM_DEPOSIT: u128 = 10;
+		const MAX_CLAIMS_PER_ACCOUNT: Option<u32> = Some(3);
+		const MAX_BATCH_SIZE: u32 = 3;
+		const MAX_EXPIRIES_PER_BLOCK: u32 = 2;
 	}
 
-	impl crate::system::Config for TestConfig {
+	impl balances::Config for TestConfig {
+		type Balance = u128;
+	}
+
+	impl system::Config for TestConfig {
 		type AccountId = &'static str;
 		type BlockNumber = u32;
 		type Nonce = u32;
+		type RuntimeEvent = ();
+	}
+
+	impl HasPallet<system::Pallet<TestConfig>> for TestConfig {
+		fn pallet(&self) -> &system::Pallet<TestConfig> {
+			&self.system
+		}
+
+		fn pallet_mut(&mut self) -> &mut system::Pallet<TestConfig> {
+			&mut self.system
+		}
+	}
+
+	impl HasPallet<Poe> for TestConfig {
+		fn pallet(&self) -> &Poe {
+			&self.poe
+		}
+
+		fn pallet_mut(&mut self) -> &mut Poe {
+			&mut self.poe
+		}
+	}
+
+	// Like the runtime, the events emitted in a transaction are discarded when it is rolled back.
+	impl Transactional for TestConfig {
+		fn start_transaction(&mut self) {
+			self.system.start_transaction();
+			self.balances.start_transaction();
+			self.poe.start_transaction();
+			self.event_marks.push(self.poe.events.len());
+		}
+
+		fn commit_transaction(&mut self) {
+			self.system.commit_transaction();
+			self.balances.commit_transaction();
+			self.poe.commit_transaction();
+			self.event_marks.pop();
+		}
+
+		fn rollback_transaction(&mut self) {
+			self.system.rollback_transaction();
+			self.balances.rollback_transaction();
+			self.poe.rollback_transaction();
+			if let Some(len) = self.event_marks.pop() {
+				self.poe.events.truncate(len);
+			}
+		}
+	}
+
+	impl HasPallet<balances::Pallet<TestConfig>> for TestConfig {
+		fn pallet(&self) -> &balances::Pallet<TestConfig> {
+			&self.balances
+		}
+
+		fn pallet_mut(&mut self) -> &mut balances::Pallet<TestConfig> {
+			&mut self.balances
+		}
+	}
+
+	fn new_runtime() -> TestConfig {
+		let mut runtime = TestConfig {
+			system: system::Pallet::new(),
+			balances: balances::Pallet::new(),
+			poe: Poe::new(),
+			event_marks: Vec::new(),
+		};
+		for who in ["Alice", "Bob", "Charlie"] {
+			runtime.balances.set_balance(&who, 100);
+		}
+		runtime
 	}
 
 	#[test]
 	fn basic_proof_of_existence() {
-		let mut poe = super::Pallet::<TestConfig>::new();
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
 
-		let _ = poe.create_claim("Alice", "My Document");
-		assert_eq!(poe.get_claim(&"My Document"), Some(&"Alice"));
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Alice"));
 
-		let result = poe.revoke_claim("Bob", "My Document");
+		let result = Poe::revoke_claim(&mut runtime, "Bob", document);
 		assert!(result.is_err(), "caller is not the owner of the claim");
 
-		let result2 = poe.create_claim("Bob", "My Document");
+		let result2 = Poe::create_claim(&mut runtime, "Bob", document, None);
 		assert!(result2.is_err(), "claim already exists");
 
-		let result3 = poe.revoke_claim("Alice", "Not existent document");
+		let result3 = Poe::revoke_claim(&mut runtime, "Alice", Poe::hash_content(b"Not existent document"));
 		assert!(result3.is_err(), "claim does not exist");
 
-		let result4 = poe.revoke_claim("Alice", "My Document");
+		let result4 = Poe::revoke_claim(&mut runtime, "Alice", document);
 		assert!(result4.is_ok(), "should be able to revoke the claim");
-		assert_eq!(poe.get_claim(&"My Document"), None, "claim should be removed after revocation");
+		assert_eq!(runtime.poe.get_claim(&document), None, "claim should be removed after revocation");
+
+	}
+
+	#[test]
+	fn claim_info_records_block_number() {
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
+
+		runtime.system.inc_block_number();
+		runtime.system.inc_block_number();
+		runtime.system.set_now(Some(1_700_000_000));
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+
+		let info = runtime.poe.claim_info(&document).expect("claim should exist");
+		assert_eq!(info.owner, "Alice");
+		assert_eq!(info.block_number, 2);
+		assert_eq!(info.timestamp, Some(1_700_000_000));
+		assert_eq!(runtime.poe.claim_info(&Poe::hash_content(b"Other Document")), None);
+	}
+
+	#[test]
+	fn transfer_claim() {
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
+
+		let result = Poe::transfer_claim(&mut runtime, "Alice", document, "Bob");
+		assert_eq!(result, Err("Claim does not exist"));
+
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+		let result = Poe::transfer_claim(&mut runtime, "Bob", document, "Bob");
+		assert_eq!(result, Err("Caller is not the owner of the claim"));
+
+		assert_eq!(Poe::transfer_claim(&mut runtime, "Alice", document, "Bob"), Ok(()));
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Bob"));
+		assert_eq!(Poe::revoke_claim(&mut runtime, "Alice", document), Err("Caller is not the owner of the claim"));
+	}
+
+	#[test]
+	fn offer_and_accept_claim() {
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+
+		let result = runtime.poe.offer_claim("Bob", document, "Bob");
+		assert_eq!(result, Err("Caller is not the owner of the claim"));
+		let result = Poe::accept_claim(&mut runtime, "Bob", document);
+		assert_eq!(result, Err("Claim has not been offered to the caller"));
+
+		assert_eq!(runtime.poe.offer_claim("Alice", document, "Bob"), Ok(()));
+		assert_eq!(runtime.poe.offer(&document), Some(&"Bob"));
+		// The claim does not change hands until the offer is accepted.
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Alice"));
+		let result = Poe::accept_claim(&mut runtime, "Charlie", document);
+		assert_eq!(result, Err("Claim has not been offered to the caller"));
+
+		assert_eq!(Poe::accept_claim(&mut runtime, "Bob", document), Ok(()));
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Bob"));
+		assert_eq!(runtime.poe.offer(&document), None);
+	}
+
+	#[test]
+	fn cancelled_offer_cannot_be_accepted() {
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+
+		assert_eq!(runtime.poe.cancel_offer("Alice", document), Err("Claim has not been offered"));
+		let _ = runtime.poe.offer_claim("Alice", document, "Bob");
+		assert_eq!(runtime.poe.cancel_offer("Bob", document), Err("Caller is not the owner of the claim"));
+		assert_eq!(runtime.poe.cancel_offer("Alice", document), Ok(()));
+		assert_eq!(Poe::accept_claim(&mut runtime, "Bob", document), Err("Claim has not been offered to the caller"));
+
+		// Transferring the claim directly also clears any pending offer.
+		let _ = runtime.poe.offer_claim("Alice", document, "Bob");
+		let _ = Poe::transfer_claim(&mut runtime, "Alice", document, "Charlie");
+		assert_eq!(Poe::accept_claim(&mut runtime, "Bob", document), Err("Claim has not been offered to the caller"));
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Charlie"));
+	}
+
+	#[test]
+	fn claims_of_owner() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2", b"doc 3"].map(|doc| Poe::hash_content(doc));
+		let mut sorted = documents.to_vec();
+		sorted.sort();
+
+		for document in documents {
+			let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+		}
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 3);
+		assert_eq!(runtime.poe.claims_of(&"Alice", 0, 2), vec![&sorted[0], &sorted[1]]);
+		assert_eq!(runtime.poe.claims_of(&"Alice", 1, 2), vec![&sorted[2]]);
+		assert!(runtime.poe.claims_of(&"Alice", 2, 2).is_empty());
+		assert!(runtime.poe.claims_of(&"Bob", 0, 2).is_empty());
+
+		let _ = Poe::transfer_claim(&mut runtime, "Alice", sorted[0], "Bob");
+		let _ = Poe::revoke_claim(&mut runtime, "Alice", sorted[1]);
+		assert_eq!(runtime.poe.claims_of(&"Alice", 0, 10), vec![&sorted[2]]);
+		assert_eq!(runtime.poe.claims_of(&"Bob", 0, 10), vec![&sorted[0]]);
+
+		let _ = runtime.poe.offer_claim("Alice", sorted[2], "Bob");
+		let _ = Poe::accept_claim(&mut runtime, "Bob", sorted[2]);
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 0);
+		assert_eq!(runtime.poe.claims_of(&"Bob", 0, 10), vec![&sorted[0], &sorted[2]]);
+	}
+
+	#[test]
+	fn max_claims_per_account() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2", b"doc 3", b"doc 4"].map(|doc| Poe::hash_content(doc));
+
+		for document in &documents[..3] {
+			assert_eq!(Poe::create_claim(&mut runtime, "Alice", *document, None), Ok(()));
+		}
+		let result = Poe::create_claim(&mut runtime, "Alice", documents[3], None);
+		assert_eq!(result, Err("Too many claims for account"));
+
+		let _ = Poe::create_claim(&mut runtime, "Bob", documents[3], None);
+		let result = Poe::transfer_claim(&mut runtime, "Bob", documents[3], "Alice");
+		assert_eq!(result, Err("Too many claims for account"));
+
+		let _ = runtime.poe.offer_claim("Bob", documents[3], "Alice");
+		assert_eq!(Poe::accept_claim(&mut runtime, "Alice", documents[3]), Err("Too many claims for account"));
+		assert_eq!(runtime.poe.get_claim(&documents[3]), Some(&"Bob"));
+
+		// A claim cannot be handed to its own owner, so the cap does not get in the way.
+		let result = Poe::transfer_claim(&mut runtime, "Alice", documents[0], "Alice");
+		assert_eq!(result, Err("Cannot transfer a claim to its owner"));
+		let result = runtime.poe.offer_claim("Alice", documents[0], "Alice");
+		assert_eq!(result, Err("Cannot transfer a claim to its owner"));
+	}
+
+	#[test]
+	fn claim_deposit() {
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
+
+		runtime.balances.set_balance(&"Dave", 5);
+		let result = Poe::create_claim(&mut runtime, "Dave", document, None);
+		assert_eq!(result, Err("Insufficient balance"));
+		assert_eq!(runtime.poe.get_claim(&document), None);
+
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+		assert_eq!(runtime.poe.claim_info(&document).unwrap().deposit, 10);
+		assert_eq!(runtime.balances.free_balance(&"Alice"), 90);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 10);
+
+		// The deposit follows the claim to its new owner, who must be able to afford it.
+		let result = Poe::transfer_claim(&mut runtime, "Alice", document, "Dave");
+		assert_eq!(result, Err("Insufficient balance"));
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Alice"));
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 10);
 
+		let _ = Poe::transfer_claim(&mut runtime, "Alice", document, "Bob");
+		assert_eq!(runtime.balances.free_balance(&"Alice"), 100);
+		assert_eq!(runtime.balances.reserved_balance(&"Bob"), 10);
+
+		let _ = Poe::revoke_claim(&mut runtime, "Bob", document);
+		assert_eq!(runtime.balances.free_balance(&"Bob"), 100);
+		assert_eq!(runtime.balances.reserved_balance(&"Bob"), 0);
+	}
+
+	#[test]
+	fn failed_deposit_return_keeps_new_owner_funds() {
+		let mut runtime = new_runtime();
+		let document = Poe::hash_content(b"My Document");
+		let _ = Poe::create_claim(&mut runtime, "Alice", document, None);
+		let _ = runtime.poe.offer_claim("Alice", document, "Bob");
+		// Without its deposit reserved, the claim cannot change hands.
+		let _ = runtime.balances.unreserve(&"Alice", 10);
+
+		let result = Poe::transfer_claim(&mut runtime, "Alice", document, "Charlie");
+		assert_eq!(result, Err("Insufficient reserved balance"));
+		assert_eq!(runtime.balances.free_balance(&"Charlie"), 100);
+		assert_eq!(runtime.balances.reserved_balance(&"Charlie"), 0);
+
+		let result = Poe::accept_claim(&mut runtime, "Bob", document);
+		assert_eq!(result, Err("Insufficient reserved balance"));
+		assert_eq!(runtime.balances.free_balance(&"Bob"), 100);
+		assert_eq!(runtime.balances.reserved_balance(&"Bob"), 0);
+		assert_eq!(runtime.poe.get_claim(&document), Some(&"Alice"));
+	}
+
+	#[test]
+	fn expired_claims_are_removed() {
+		let mut runtime = new_runtime();
+		let expiring = Poe::hash_content(b"Certificate");
+		let permanent = Poe::hash_content(b"Deed");
+
+		runtime.system.inc_block_number();
+		let _ = Poe::create_claim(&mut runtime, "Alice", expiring, Some(2));
+		let _ = Poe::create_claim(&mut runtime, "Alice", permanent, None);
+		assert_eq!(runtime.poe.claim_info(&expiring).unwrap().expires_at, Some(3));
+		assert_eq!(runtime.poe.claim_info(&permanent).unwrap().expires_at, None);
+
+		Poe::on_finalize(&mut runtime);
+		runtime.system.inc_block_number();
+		Poe::on_finalize(&mut runtime);
+		assert!(runtime.poe.get_claim(&expiring).is_some(), "claim expires at the end of block 3");
+
+		runtime.system.inc_block_number();
+		Poe::on_finalize(&mut runtime);
+		assert_eq!(runtime.poe.get_claim(&expiring), None);
+		assert_eq!(runtime.poe.get_claim(&permanent), Some(&"Alice"));
+		assert_eq!(runtime.poe.claims_of(&"Alice", 0, 10), vec![&permanent]);
+		assert_eq!(runtime.poe.expiring_at(&3), None);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 10, "deposit is returned");
+		assert_eq!(
+			runtime.poe.take_events().last(),
+			Some(&Event::ClaimExpired { owner: "Alice", claim: expiring })
+		);
+	}
+
+	#[test]
+	fn failed_expiry_is_deferred() {
+		let mut runtime = new_runtime();
+		let expiring = Poe::hash_content(b"Certificate");
+		let _ = Poe::create_claim(&mut runtime, "Alice", expiring, Some(0));
+		// Without its deposit reserved, the claim cannot be removed.
+		let _ = runtime.balances.unreserve(&"Alice", 10);
+
+		Poe::on_finalize(&mut runtime);
+		assert_eq!(runtime.poe.get_claim(&expiring), Some(&"Alice"));
+		assert_eq!(runtime.poe.claim_info(&expiring).unwrap().expires_at, Some(1));
+		assert_eq!(runtime.poe.expiring_at(&0), None);
+		assert_eq!(
+			runtime.poe.take_events().last(),
+			Some(&Event::ExpiryDeferred {
+				claim: expiring,
+				expires_at: 1,
+				error: "Insufficient reserved balance",
+			})
+		);
+
+		let _ = runtime.balances.reserve(&"Alice", 10);
+		runtime.system.inc_block_number();
+		Poe::on_finalize(&mut runtime);
+		assert_eq!(runtime.poe.get_claim(&expiring), None);
+		assert_eq!(runtime.poe.expiring_at(&1), None);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 0);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn expiries_are_bounded_per_block() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2", b"doc 3"].map(|doc| Poe::hash_content(doc));
+
+		for document in documents {
+			let _ = Poe::create_claim(&mut runtime, "Alice", document, Some(0));
+		}
+
+		// Only `MAX_EXPIRIES_PER_BLOCK` claims are removed, the rest follow in the next block.
+		Poe::on_finalize(&mut runtime);
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 1);
+		runtime.system.inc_block_number();
+		Poe::on_finalize(&mut runtime);
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 0);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 0);
+	}
+
+	#[test]
+	fn renew_claim() {
+		let mut runtime = new_runtime();
+		let expiring = Poe::hash_content(b"Certificate");
+		let permanent = Poe::hash_content(b"Deed");
+		let _ = Poe::create_claim(&mut runtime, "Alice", expiring, Some(1));
+		let _ = Poe::create_claim(&mut runtime, "Alice", permanent, None);
+
+		assert_eq!(runtime.poe.renew_claim("Bob", expiring, 5), Err("Caller is not the owner of the claim"));
+		assert_eq!(runtime.poe.renew_claim("Alice", permanent, 5), Err("Claim does not expire"));
+		assert_eq!(runtime.poe.renew_claim("Alice", expiring, 5), Ok(()));
+		assert_eq!(runtime.poe.claim_info(&expiring).unwrap().expires_at, Some(6));
+		assert_eq!(runtime.poe.expiring_at(&1), None);
+
+		runtime.system.inc_block_number();
+		Poe::on_finalize(&mut runtime);
+		assert_eq!(runtime.poe.get_claim(&expiring), Some(&"Alice"));
+
+		// A revoked claim no longer expires.
+		let _ = Poe::revoke_claim(&mut runtime, "Alice", expiring);
+		assert_eq!(runtime.poe.expiring_at(&6), None);
+	}
+
+	#[test]
+	fn co_signed_claim_with_all_signers() {
+		let mut runtime = new_runtime();
+		let contract = Poe::hash_content(b"Contract");
+
+		let result = Poe::propose_claim(&mut runtime, "Alice", contract, vec!["Bob"], Some(2));
+		assert_eq!(result, Err("Invalid threshold"));
+		let signers = vec!["Bob", "Charlie"];
+		assert_eq!(Poe::propose_claim(&mut runtime, "Alice", contract, signers, None), Ok(()));
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 10);
+
+		let result = Poe::create_claim(&mut runtime, "Dave", contract, None);
+		assert_eq!(result, Err("Claim already proposed"));
+		let result = Poe::attest(&mut runtime, "Dave", contract);
+		assert_eq!(result, Err("Caller is not a signer of the claim"));
+
+		assert_eq!(Poe::attest(&mut runtime, "Bob", contract), Ok(()));
+		assert_eq!(Poe::attest(&mut runtime, "Bob", contract), Err("Caller already attested"));
+		assert_eq!(runtime.poe.get_claim(&contract), None, "claim is not final yet");
+
+		assert_eq!(Poe::attest(&mut runtime, "Charlie", contract), Ok(()));
+		assert_eq!(runtime.poe.get_claim(&contract), Some(&"Alice"));
+		assert_eq!(runtime.poe.claim_info(&contract).unwrap().deposit, 10);
+		assert_eq!(runtime.poe.proposal(&contract), None);
+		let attestations = runtime.poe.attestations(&contract).unwrap();
+		assert_eq!(attestations.iter().collect::<Vec<_>>(), vec![&"Bob", &"Charlie"]);
+		assert_eq!(Poe::attest(&mut runtime, "Bob", contract), Err("Claim has not been proposed"));
+
+		// Revoking the final claim returns the deposit, and forgets the attestations.
+		let _ = Poe::revoke_claim(&mut runtime, "Alice", contract);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 0);
+		assert_eq!(runtime.poe.attestations(&contract), None);
+	}
+
+	#[test]
+	fn co_signed_claim_with_threshold() {
+		let mut runtime = new_runtime();
+		let contract = Poe::hash_content(b"Contract");
+		let signers = vec!["Alice", "Bob", "Charlie"];
+
+		let _ = Poe::propose_claim(&mut runtime, "Alice", contract, signers, Some(2));
+		assert_eq!(runtime.poe.proposal(&contract).unwrap().threshold, 2);
+
+		let _ = Poe::attest(&mut runtime, "Alice", contract);
+		assert_eq!(runtime.poe.get_claim(&contract), None);
+		let _ = Poe::attest(&mut runtime, "Charlie", contract);
+		assert_eq!(runtime.poe.get_claim(&contract), Some(&"Alice"));
+		assert_eq!(runtime.poe.attestations(&contract).unwrap().len(), 2);
+	}
+
+	#[test]
+	fn cancel_co_signed_claim() {
+		let mut runtime = new_runtime();
+		let contract = Poe::hash_content(b"Contract");
+
+		let _ = Poe::propose_claim(&mut runtime, "Alice", contract, vec!["Bob"], None);
+		let result = Poe::cancel_proposal(&mut runtime, "Bob", contract);
+		assert_eq!(result, Err("Caller is not the creator of the claim"));
+
+		assert_eq!(Poe::cancel_proposal(&mut runtime, "Alice", contract), Ok(()));
+		assert_eq!(runtime.poe.proposal(&contract), None);
+		assert_eq!(runtime.poe.attestations(&contract), None);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 0);
+		assert_eq!(Poe::attest(&mut runtime, "Bob", contract), Err("Claim has not been proposed"));
+	}
+
+	#[test]
+	fn co_signed_claim_counts_towards_max_claims() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2", b"doc 3", b"doc 4"].map(|doc| Poe::hash_content(doc));
+
+		for document in &documents[..2] {
+			assert_eq!(Poe::create_claim(&mut runtime, "Alice", *document, None), Ok(()));
+		}
+		let result = Poe::propose_claim(&mut runtime, "Alice", documents[2], vec!["Bob"], None);
+		assert_eq!(result, Ok(()));
+
+		// The pending proposal keeps the last slot of Alice for the claim.
+		let result = Poe::create_claim(&mut runtime, "Alice", documents[3], None);
+		assert_eq!(result, Err("Too many claims for account"));
+		let result = Poe::propose_claim(&mut runtime, "Alice", documents[3], vec!["Bob"], None);
+		assert_eq!(result, Err("Too many claims for account"));
+
+		// Cancelling the proposal frees the slot.
+		assert_eq!(Poe::cancel_proposal(&mut runtime, "Alice", documents[2]), Ok(()));
+		assert_eq!(Poe::create_claim(&mut runtime, "Alice", documents[3], None), Ok(()));
+		let _ = Poe::revoke_claim(&mut runtime, "Alice", documents[3]);
+
+		// The final attestation succeeds while Alice is at the limit.
+		let result = Poe::propose_claim(&mut runtime, "Alice", documents[2], vec!["Bob"], None);
+		assert_eq!(result, Ok(()));
+		assert_eq!(Poe::attest(&mut runtime, "Bob", documents[2]), Ok(()));
+		assert_eq!(runtime.poe.get_claim(&documents[2]), Some(&"Alice"));
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 3);
+		let result = Poe::create_claim(&mut runtime, "Alice", documents[3], None);
+		assert_eq!(result, Err("Too many claims for account"));
+	}
+
+	#[test]
+	fn batch_create_and_revoke() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2", b"doc 3"].map(|doc| Poe::hash_content(doc));
+
+		assert_eq!(Poe::create_claims(&mut runtime, "Alice", documents.to_vec()), Ok(()));
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 3);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 30);
+
+		let result = Poe::revoke_claims(&mut runtime, "Alice", vec![documents[0], documents[0]]);
+		assert_eq!(result, Err("Duplicate claim in batch"));
+		let result = Poe::revoke_claims(&mut runtime, "Bob", documents[..2].to_vec());
+		assert_eq!(result, Err("Caller is not the owner of the claim"));
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 3);
+
+		assert_eq!(Poe::revoke_claims(&mut runtime, "Alice", documents[..2].to_vec()), Ok(()));
+		assert_eq!(runtime.poe.claims_of(&"Alice", 0, 10), vec![&documents[2]]);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 10);
+	}
+
+	#[test]
+	fn batch_create_is_all_or_nothing() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2", b"doc 3", b"doc 4"].map(|doc| Poe::hash_content(doc));
+		let _ = Poe::create_claim(&mut runtime, "Bob", documents[2], None);
+
+		let result = Poe::create_claims(&mut runtime, "Alice", documents.to_vec());
+		assert_eq!(result, Err("Too many claims in batch"));
+
+		let result = Poe::create_claims(&mut runtime, "Alice", documents[..3].to_vec());
+		assert_eq!(result, Err("Claim already exists"));
+		assert_eq!(runtime.poe.claim_count(&"Alice"), 0);
+		assert_eq!(runtime.poe.get_claim(&documents[0]), None);
+		assert_eq!(runtime.balances.free_balance(&"Alice"), 100);
+		assert_eq!(
+			runtime.poe.take_events(),
+			vec![Event::ClaimCreated { owner: "Bob", claim: documents[2] }],
+			"no events are kept for a failed batch"
+		);
+
+		runtime.balances.set_balance(&"Dave", 15);
+		let result = Poe::create_claims(&mut runtime, "Dave", documents[..2].to_vec());
+		assert_eq!(result, Err("Insufficient balance"));
+		assert_eq!(runtime.poe.claim_count(&"Dave"), 0);
+		assert_eq!(runtime.balances.free_balance(&"Dave"), 15);
+	}
+
+	#[test]
+	fn batch_revoke_is_all_or_nothing() {
+		let mut runtime = new_runtime();
+		let documents = [b"doc 1", b"doc 2"].map(|doc| Poe::hash_content(doc));
+		assert_eq!(Poe::create_claims(&mut runtime, "Alice", documents.to_vec()), Ok(()));
+		runtime.poe.take_events();
+
+		// Only the deposit of the first claim can still be returned.
+		assert_eq!(runtime.balances.unreserve(&"Alice", 5), Ok(()));
+		let result = Poe::revoke_claims(&mut runtime, "Alice", documents.to_vec());
+		assert_eq!(result, Err("Insufficient reserved balance"));
+		assert_eq!(runtime.poe.claims_of(&"Alice", 0, 10), vec![&documents[0], &documents[1]]);
+		assert_eq!(runtime.balances.reserved_balance(&"Alice"), 15);
+		assert!(runtime.poe.take_events().is_empty());
+	}
+
+	#[test]
+	fn hash_file_matches_hash_content() {
+		let path = std::env::temp_dir().join("ironledger_poe_hash_file.txt");
+		std::fs::write(&path, b"My Document").unwrap();
+
+		let hash = Poe::hash_file(&path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+
+		assert_eq!(hash, Poe::hash_content(b"My Document"));
+		assert_ne!(hash, Poe::hash_content(b"My Other Document"));
+		assert!(Poe::hash_file(&path).is_err(), "missing file cannot be hashed");
+	}
+}
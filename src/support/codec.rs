@@ -0,0 +1,250 @@
+use super::{ExecutionMode, H256};
+use std::collections::BTreeSet;
+
+/// Implemented by the types stored in storage items, to be written as raw bytes.
+///
+/// Integers are written in little endian, and the length of collections is written as a `u32`
+/// before their items.
+pub trait Encode {
+	/// Append the encoding of this value to `dest`.
+	fn encode_to(&self, dest: &mut Vec<u8>);
+
+	/// Encode this value into a new `Vec`.
+	fn encode(&self) -> Vec<u8> {
+		let mut dest = Vec::new();
+		self.encode_to(&mut dest);
+		dest
+	}
+}
+
+/// Implemented by the types stored in storage items, to be read back from raw bytes. This is the
+/// reverse of `Encode`.
+pub trait Decode: Sized {
+	/// Read a value from the start of `input`, and advance `input` past it. Returns `None` if
+	/// `input` does not start with a valid encoding.
+	fn decode(input: &mut &[u8]) -> Option<Self>;
+}
+
+/// Decode a value which must take up all of `input`.
+pub fn decode_all<T: Decode>(mut input: &[u8]) -> Option<T> {
+	let value = T::decode(&mut input)?;
+	input.is_empty().then_some(value)
+}
+
+/// Take the first `len` bytes of `input`.
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+	if input.len() < len {
+		return None;
+	}
+	let (bytes, rest) = input.split_at(len);
+	*input = rest;
+	Some(bytes)
+}
+
+macro_rules! codec_int {
+	($($t:ty),*) => {
+		$(
+			impl Encode for $t {
+				fn encode_to(&self, dest: &mut Vec<u8>) {
+					dest.extend_from_slice(&self.to_le_bytes());
+				}
+			}
+
+			impl Decode for $t {
+				fn decode(input: &mut &[u8]) -> Option<Self> {
+					let bytes = take(input, core::mem::size_of::<$t>())?;
+					Some(<$t>::from_le_bytes(bytes.try_into().ok()?))
+				}
+			}
+		)*
+	};
+}
+
+codec_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Encode for bool {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		dest.push(*self as u8);
+	}
+}
+
+impl Decode for bool {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		match u8::decode(input)? {
+			0 => Some(false),
+			1 => Some(true),
+			_ => None,
+		}
+	}
+}
+
+impl Encode for () {
+	fn encode_to(&self, _dest: &mut Vec<u8>) {}
+}
+
+impl Decode for () {
+	fn decode(_input: &mut &[u8]) -> Option<Self> {
+		Some(())
+	}
+}
+
+/// Write the length of a collection, which must fit in a `u32`.
+fn encode_len(len: usize, dest: &mut Vec<u8>) {
+	u32::try_from(len).expect("collections are shorter than u32::MAX").encode_to(dest);
+}
+
+impl Encode for str {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		encode_len(self.len(), dest);
+		dest.extend_from_slice(self.as_bytes());
+	}
+}
+
+impl Encode for &str {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		(**self).encode_to(dest)
+	}
+}
+
+impl Encode for String {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.as_str().encode_to(dest)
+	}
+}
+
+impl Decode for String {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		let len = u32::decode(input)? as usize;
+		String::from_utf8(take(input, len)?.to_vec()).ok()
+	}
+}
+
+impl<T: Encode> Encode for [T] {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		encode_len(self.len(), dest);
+		self.iter().for_each(|item| item.encode_to(dest));
+	}
+}
+
+impl<T: Encode> Encode for Vec<T> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.as_slice().encode_to(dest)
+	}
+}
+
+impl<T: Decode> Decode for Vec<T> {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		let len = u32::decode(input)? as usize;
+		// Every item takes at least one byte, except for empty types, so a length larger than the
+		// input is only trusted up to the size of the input.
+		let mut items = Vec::with_capacity(len.min(input.len()));
+		for _ in 0..len {
+			items.push(T::decode(input)?);
+		}
+		Some(items)
+	}
+}
+
+impl<T: Encode> Encode for BTreeSet<T> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		encode_len(self.len(), dest);
+		self.iter().for_each(|item| item.encode_to(dest));
+	}
+}
+
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some(Vec::<T>::decode(input)?.into_iter().collect())
+	}
+}
+
+impl<T: Encode> Encode for Option<T> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		match self {
+			Some(value) => {
+				dest.push(1);
+				value.encode_to(dest);
+			},
+			None => dest.push(0),
+		}
+	}
+}
+
+impl<T: Decode> Decode for Option<T> {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		match u8::decode(input)? {
+			0 => Some(None),
+			1 => Some(Some(T::decode(input)?)),
+			_ => None,
+		}
+	}
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.0.encode_to(dest);
+		self.1.encode_to(dest);
+	}
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some((A::decode(input)?, B::decode(input)?))
+	}
+}
+
+impl Encode for H256 {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		dest.extend_from_slice(&self.0);
+	}
+}
+
+impl Decode for H256 {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		Some(H256(take(input, 32)?.try_into().ok()?))
+	}
+}
+
+impl Encode for ExecutionMode {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		match self {
+			ExecutionMode::Lenient => dest.push(0),
+			ExecutionMode::Strict => dest.push(1),
+		}
+	}
+}
+
+impl Decode for ExecutionMode {
+	fn decode(input: &mut &[u8]) -> Option<Self> {
+		match u8::decode(input)? {
+			0 => Some(ExecutionMode::Lenient),
+			1 => Some(ExecutionMode::Strict),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		assert_eq!(300u16.encode(), vec![44, 1]);
+		assert_eq!("ab".encode(), vec![2, 0, 0, 0, b'a', b'b']);
+		assert_eq!(Some(true).encode(), vec![1, 1]);
+
+		let value = (vec![Some("alice".to_string()), None], BTreeSet::from([3u32, 1]));
+		assert_eq!(decode_all(&value.encode()), Some(value));
+		assert_eq!(decode_all::<H256>(&H256([7; 32]).encode()), Some(H256([7; 32])));
+	}
+
+	#[test]
+	fn invalid_input() {
+		assert_eq!(decode_all::<u32>(&[1, 0, 0]), None);
+		assert_eq!(decode_all::<u8>(&[1, 0]), None, "all of the input must be used");
+		assert_eq!(decode_all::<bool>(&[2]), None);
+		assert_eq!(decode_all::<String>(&[1, 0, 0, 0, 0xff]), None);
+		assert_eq!(decode_all::<Vec<u8>>(&[0xff, 0xff, 0xff, 0xff]), None);
+	}
+}
@@ -0,0 +1,194 @@
+/// A machine readable description of a runtime, generated by the `#[macros::runtime]` macro, so
+/// that clients can learn which calls exist without reading the source of the runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeMetadata {
+	/// The pallets of the runtime, in the order of the `Runtime` struct.
+	pub pallets: Vec<PalletMetadata>,
+}
+
+/// The description of a pallet included in a runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalletMetadata {
+	/// The name of the pallet in the `Runtime` struct, which is also its `RuntimeCall` variant.
+	pub name: &'static str,
+	/// The position of the pallet in the `Runtime` struct, where `system` is 0.
+	pub index: u32,
+	/// The callable functions of the pallet.
+	pub calls: Vec<CallMetadata>,
+	/// The events emitted by the pallet.
+	pub events: Vec<EventMetadata>,
+	/// The errors which can be returned by the calls of the pallet.
+	pub errors: Vec<&'static str>,
+}
+
+/// The description of a callable function, generated by the `#[macros::call]` macro.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallMetadata {
+	/// The name of the function, which is also its `Call` variant.
+	pub name: &'static str,
+	/// The stable index of the function in the pallet, given with `#[call_index(..)]` or following
+	/// the previous function.
+	pub index: u32,
+	/// The arguments of the function, not including the `caller`.
+	pub args: Vec<FieldMetadata>,
+	/// The doc comments of the function, one line per entry.
+	pub docs: Vec<&'static str>,
+}
+
+/// The description of an event, generated by `#[derive(macros::EventMetadata)]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventMetadata {
+	/// The name of the event variant.
+	pub name: &'static str,
+	/// The fields of the event.
+	pub fields: Vec<FieldMetadata>,
+	/// The doc comments of the event, one line per entry.
+	pub docs: Vec<&'static str>,
+}
+
+/// The description of a named argument or field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMetadata {
+	pub name: &'static str,
+	/// The type as written in the source, such as `T::AccountId`.
+	pub ty: &'static str,
+	/// The concrete type in the runtime, such as `alloc::string::String`.
+	pub type_name: &'static str,
+}
+
+/// Implemented by the `Call` enum of a pallet by the `#[macros::call]` macro.
+pub trait GetCallMetadata {
+	/// The description of every callable function of the pallet.
+	fn call_metadata() -> Vec<CallMetadata>;
+	/// The errors declared by the pallet with the `error` argument of `#[macros::call]`.
+	fn error_metadata() -> Vec<&'static str>;
+}
+
+/// Implemented by the `Error` enum of a pallet by `#[derive(macros::Error)]`.
+pub trait GetErrorMetadata {
+	/// The message of every error of the pallet.
+	fn error_metadata() -> Vec<&'static str>;
+}
+
+/// Implemented by the `Event` enum of a pallet by `#[derive(macros::EventMetadata)]`.
+pub trait GetEventMetadata {
+	/// The description of every event of the pallet.
+	fn event_metadata() -> Vec<EventMetadata>;
+}
+
+impl RuntimeMetadata {
+	/// Encode the metadata as a JSON document.
+	pub fn to_json(&self) -> String {
+		let pallets = self.pallets.iter().map(PalletMetadata::to_json).collect::<Vec<_>>();
+		format!("{{\"pallets\":[{}]}}", pallets.join(","))
+	}
+}
+
+impl PalletMetadata {
+	fn to_json(&self) -> String {
+		let calls = self.calls.iter().map(CallMetadata::to_json).collect::<Vec<_>>();
+		let events = self.events.iter().map(EventMetadata::to_json).collect::<Vec<_>>();
+		format!(
+			"{{\"name\":{},\"index\":{},\"calls\":[{}],\"events\":[{}],\"errors\":{}}}",
+			json_string(self.name),
+			self.index,
+			calls.join(","),
+			events.join(","),
+			json_strings(&self.errors),
+		)
+	}
+}
+
+impl CallMetadata {
+	fn to_json(&self) -> String {
+		format!(
+			"{{\"name\":{},\"index\":{},\"args\":{},\"docs\":{}}}",
+			json_string(self.name),
+			self.index,
+			json_fields(&self.args),
+			json_strings(&self.docs),
+		)
+	}
+}
+
+impl EventMetadata {
+	fn to_json(&self) -> String {
+		format!(
+			"{{\"name\":{},\"fields\":{},\"docs\":{}}}",
+			json_string(self.name),
+			json_fields(&self.fields),
+			json_strings(&self.docs),
+		)
+	}
+}
+
+/// Encode a list of fields as a JSON array of objects.
+fn json_fields(fields: &[FieldMetadata]) -> String {
+	let fields = fields
+		.iter()
+		.map(|field| {
+			format!(
+				"{{\"name\":{},\"type\":{},\"type_name\":{}}}",
+				json_string(field.name),
+				json_string(field.ty),
+				json_string(field.type_name),
+			)
+		})
+		.collect::<Vec<_>>();
+	format!("[{}]", fields.join(","))
+}
+
+/// Encode a list of strings as a JSON array.
+fn json_strings(strings: &[&str]) -> String {
+	let strings = strings.iter().map(|s| json_string(s)).collect::<Vec<_>>();
+	format!("[{}]", strings.join(","))
+}
+
+/// Encode a string as a JSON string, escaping the characters JSON does not allow in it.
+fn json_string(s: &str) -> String {
+	let mut json = String::with_capacity(s.len() + 2);
+	json.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => json.push_str("\\\""),
+			'\\' => json.push_str("\\\\"),
+			'\n' => json.push_str("\\n"),
+			c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+			c => json.push(c),
+		}
+	}
+	json.push('"');
+	json
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encode_json() {
+		let metadata = RuntimeMetadata {
+			pallets: vec![PalletMetadata {
+				name: "balances",
+				index: 1,
+				calls: vec![CallMetadata {
+					name: "transfer",
+					index: 0,
+					args: vec![FieldMetadata { name: "to", ty: "T::AccountId", type_name: "String" }],
+					docs: vec!["Transfer \"funds\"."],
+				}],
+				events: vec![],
+				errors: vec!["Insufficient balance"],
+			}],
+		};
+
+		assert_eq!(
+			metadata.to_json(),
+			concat!(
+				r#"{"pallets":[{"name":"balances","index":1,"calls":[{"name":"transfer","index":0,"#,
+				r#""args":[{"name":"to","type":"T::AccountId","type_name":"String"}],"#,
+				r#""docs":["Transfer \"funds\"."]}],"events":[],"errors":["Insufficient balance"]}]}"#,
+			)
+		);
+	}
+}
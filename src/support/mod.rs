@@ -0,0 +1,374 @@
+pub mod codec;
+pub mod metadata;
+pub mod parse;
+pub mod storage;
+
+/// The most primitive representation of a Blockchain block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<Header, Extrinsic> {
+	/// The block header contains metadata about the block.
+	pub header: Header,
+	/// The extrinsics represent the state transitions to be executed in this block.
+	pub extrinsics: Vec<Extrinsic>,
+}
+
+/// We are using an extremely simplified header which only contains the current block number.
+/// On a real blockchain, you would expect to also find:
+/// - parent block hash
+/// - state root
+/// - extrinsics root
+/// - etc...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header<BlockNumber> {
+	pub block_number: BlockNumber,
+	/// The time at which the block was produced, if its author set one. This is the only source
+	/// of time of the runtime, so that executing the same block again gives the same state.
+	pub timestamp: Option<Moment>,
+}
+
+/// A point in time, in seconds since the unix epoch.
+pub type Moment = u64;
+
+/// This is an "extrinsic": literally an external message from outside of the blockchain.
+/// This simplified version of an extrinsic tells us who is making the call, and which call they are
+/// making.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extrinsic<Caller, Call> {
+	pub caller: Caller,
+	pub call: Call,
+}
+
+/// The Result type for our runtime. When everything completes successfully, we return `Ok(())`,
+/// otherwise return a static error message.
+pub type DispatchResult = Result<(), &'static str>;
+
+/// A measure of the resources needed to execute a call.
+pub type Weight = u64;
+
+/// The weight of a call which does not declare its own with `#[weight(..)]`.
+pub const DEFAULT_WEIGHT: Weight = 10_000;
+
+/// Implemented by calls to report their weight before being dispatched.
+pub trait GetWeight {
+	/// The weight of this call.
+	fn weight(&self) -> Weight;
+}
+
+/// Implemented by the arguments of calls, to display calls in a human readable way such as
+/// `balances.transfer(to: dayitva, amount: 30)`.
+pub trait DisplayArg {
+	/// Write this argument as it appears in the displayed call.
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result;
+}
+
+macro_rules! display_arg_as_display {
+	($($t:ty),*) => {
+		$(
+			impl DisplayArg for $t {
+				fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+					core::fmt::Display::fmt(self, f)
+				}
+			}
+		)*
+	};
+}
+
+display_arg_as_display!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool, H256);
+
+/// A string is written as a bare word when it is one, and otherwise quoted with `"` and `\`
+/// escaped, so that it parses back to the same string.
+impl DisplayArg for str {
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		if parse::is_bare_word(self) {
+			return f.write_str(self);
+		}
+
+		write!(f, "\"")?;
+		for c in self.chars() {
+			if c == '"' || c == '\\' {
+				write!(f, "\\")?;
+			}
+			write!(f, "{}", c)?;
+		}
+		write!(f, "\"")
+	}
+}
+
+impl DisplayArg for String {
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		self.as_str().fmt_arg(f)
+	}
+}
+
+impl DisplayArg for &str {
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		(**self).fmt_arg(f)
+	}
+}
+
+impl<T: DisplayArg> DisplayArg for Option<T> {
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Some(arg) => {
+				write!(f, "Some(")?;
+				arg.fmt_arg(f)?;
+				write!(f, ")")
+			},
+			None => write!(f, "None"),
+		}
+	}
+}
+
+impl<T: DisplayArg> DisplayArg for Vec<T> {
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "[")?;
+		for (i, arg) in self.iter().enumerate() {
+			if i > 0 {
+				write!(f, ", ")?;
+			}
+			arg.fmt_arg(f)?;
+		}
+		write!(f, "]")
+	}
+}
+
+impl<A: DisplayArg, B: DisplayArg> DisplayArg for (A, B) {
+	fn fmt_arg(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "(")?;
+		self.0.fmt_arg(f)?;
+		write!(f, ", ")?;
+		self.1.fmt_arg(f)?;
+		write!(f, ")")
+	}
+}
+
+/// How a block is executed when some of its extrinsics fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionMode {
+	/// Failing extrinsics are recorded in the receipt of the block, which is still executed.
+	#[default]
+	Lenient,
+	/// The whole block is rejected if any of its extrinsics fails, and the state is rolled back
+	/// to what it was before the block.
+	Strict,
+}
+
+/// The outcome of a single extrinsic, as executed in a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtrinsicReceipt<Caller, Event> {
+	/// The position of the extrinsic in the block.
+	pub index: u32,
+	/// The caller of the extrinsic.
+	pub caller: Caller,
+	/// The result of dispatching the call of the extrinsic.
+	pub result: DispatchResult,
+	/// The events emitted while the extrinsic was dispatched.
+	pub events: Vec<Event>,
+	/// The weight declared by the call of the extrinsic with `#[weight(..)]`. It is known before
+	/// dispatching, and is not a measure of the work the call actually did.
+	pub weight: Weight,
+}
+
+/// The outcome of an extrinsic which was executed without committing its changes to the state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRun<Event> {
+	/// The result of dispatching the call of the extrinsic.
+	pub result: DispatchResult,
+	/// The events which would be emitted by the extrinsic.
+	pub events: Vec<Event>,
+	/// The weight declared by the call of the extrinsic with `#[weight(..)]`. It is known before
+	/// dispatching, and is not a measure of the work the call actually did.
+	pub weight: Weight,
+}
+
+/// The outcome of executing a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockReceipt<BlockNumber, Caller, Event> {
+	/// The number of the executed block.
+	pub block_number: BlockNumber,
+	/// The receipt of each extrinsic of the block, in order.
+	pub extrinsics: Vec<ExtrinsicReceipt<Caller, Event>>,
+	/// The events emitted by the `on_finalize` hooks at the end of the block.
+	pub finalize_events: Vec<Event>,
+}
+
+impl<BlockNumber, Caller, Event> BlockReceipt<BlockNumber, Caller, Event> {
+	/// The total declared weight of the extrinsics of the block, saturating at `Weight::MAX`.
+	pub fn total_weight(&self) -> Weight {
+		self.extrinsics.iter().fold(0, |total, receipt| total.saturating_add(receipt.weight))
+	}
+
+	/// The receipts of the extrinsics which failed.
+	pub fn failed(&self) -> impl Iterator<Item = &ExtrinsicReceipt<Caller, Event>> {
+		self.extrinsics.iter().filter(|receipt| receipt.result.is_err())
+	}
+}
+
+/// A trait which allows us to dispatch an incoming extrinsic to the appropriate state transition
+/// function call.
+pub trait Dispatch {
+	/// The type used to identify the caller of the function.
+	type Caller;
+	/// The state transition function call the caller is trying to access.
+	type Call;
+
+	/// This function takes a `caller` and the `call` they want to make, and returns a `Result`
+	/// based on the outcome of that function call.
+	fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
+}
+
+/// The pallet level counterpart of `Dispatch`.
+///
+/// A pallet is dispatched with the `Runtime` it is part of. Calls which only need the state of the
+/// pallet are made on the pallet, borrowed from the runtime, while calls which need the state of
+/// other pallets are handed the whole runtime, and reach every pallet, including their own, through
+/// `HasPallet`.
+pub trait PalletDispatch<Runtime> {
+	/// The type used to identify the caller of the function.
+	type Caller;
+	/// The state transition function call the caller is trying to access.
+	type Call;
+
+	/// This function takes the `runtime`, a `caller` and the `call` they want to make, and
+	/// returns a `Result` based on the outcome of that function call.
+	fn dispatch(runtime: &mut Runtime, caller: Self::Caller, call: Self::Call) -> DispatchResult;
+}
+
+/// Hooks which let a pallet run logic as part of block execution, outside of any extrinsic.
+///
+/// Like the calls of `PalletDispatch` which need other pallets, each hook is handed the `Runtime`
+/// the pallet is part of.
+pub trait Hooks<Runtime> {
+	/// Called once all the extrinsics of the current block have been executed.
+	fn on_finalize(_runtime: &mut Runtime) {}
+}
+
+/// Implemented by every pallet to hand over the events it has emitted, so that the runtime can
+/// collect them in the system pallet.
+pub trait TakeEvents {
+	/// The type of the events emitted by this pallet.
+	type Event;
+
+	/// Remove and return all the events emitted since the last call.
+	fn take_events(&mut self) -> Vec<Self::Event>;
+}
+
+/// Implemented by the runtime for every pallet it is built from, so that pallets can be given
+/// access to each other without depending on the concrete `Runtime` struct.
+pub trait HasPallet<Pallet> {
+	/// Get a reference to the pallet.
+	fn pallet(&self) -> &Pallet;
+	/// Get a mutable reference to the pallet.
+	fn pallet_mut(&mut self) -> &mut Pallet;
+}
+
+/// Implemented by the markers of the instances of a pallet, so that the storage items of each
+/// instance get their own prefix.
+pub trait Instance {
+	/// Inserted in the prefix of every storage item of the instance, after the module of the
+	/// pallet. This is empty for the default instance, so its prefixes are left unchanged.
+	const PREFIX: &'static str;
+}
+
+impl Instance for () {
+	const PREFIX: &'static str = "";
+}
+
+/// A marker for the first extra instance of a pallet which can be included several times in a
+/// runtime, like `balances::Pallet<Runtime, Instance1>`. The default instance of a pallet is `()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Instance1;
+
+impl Instance for Instance1 {
+	const PREFIX: &'static str = "Instance1::";
+}
+
+/// A trait which exposes a fungible currency to the pallets which need to move funds, without
+/// them depending on the pallet which actually stores the balances.
+///
+/// A pallet can ask for a `Currency` through an associated type in its `Config`, and reach it
+/// through `HasPallet` when the runtime hands it over during dispatch.
+pub trait Currency<AccountId> {
+	/// The type used to represent an amount of this currency.
+	type Balance: core::fmt::Debug + Copy + PartialEq;
+
+	/// Get the balance of `who` which is free to be transferred or reserved.
+	fn free_balance(&self, who: &AccountId) -> Self::Balance;
+
+	/// Get the balance of `who` which is reserved, and can not be used until it is unreserved.
+	fn reserved_balance(&self, who: &AccountId) -> Self::Balance;
+
+	/// Transfer `amount` of free balance from `from` to `to`.
+	fn transfer(&mut self, from: &AccountId, to: &AccountId, amount: Self::Balance)
+		-> DispatchResult;
+
+	/// Remove `amount` from the free balance of `who`.
+	fn withdraw(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+	/// Add `amount` to the free balance of `who`.
+	fn deposit(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+	/// Move `amount` from the free balance of `who` to their reserved balance.
+	fn reserve(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+	/// Move `amount` from the reserved balance of `who` back to their free balance.
+	fn unreserve(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+}
+
+/// A fixed-size 256 bit hash, displayed as hex.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct H256(pub [u8; 32]);
+
+impl core::fmt::Display for H256 {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "0x")?;
+		for byte in self.0 {
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+impl core::fmt::Debug for H256 {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		core::fmt::Display::fmt(self, f)
+	}
+}
+
+/// A hashing algorithm, which pallets can use to store the hash of some data rather than the data
+/// itself.
+pub trait Hashing {
+	/// The type of the hash produced by this algorithm.
+	type Output: core::fmt::Debug + Ord + Clone;
+
+	/// Hash the given `data`.
+	fn hash(data: &[u8]) -> Self::Output;
+}
+
+/// The Blake2b hashing algorithm, with a 256 bit output.
+#[derive(Debug, Clone)]
+pub struct BlakeTwo256;
+
+impl Hashing for BlakeTwo256 {
+	type Output = H256;
+
+	fn hash(data: &[u8]) -> H256 {
+		use blake2::Digest;
+		H256(blake2::Blake2b::<blake2::digest::consts::U32>::digest(data).into())
+	}
+}
+
+/// The SHA-256 hashing algorithm.
+// The runtime hashes with `BlakeTwo256`, this is kept for pallets which choose SHA-256.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Sha256;
+
+impl Hashing for Sha256 {
+	type Output = H256;
+
+	fn hash(data: &[u8]) -> H256 {
+		use sha2::Digest;
+		H256(sha2::Sha256::digest(data).into())
+	}
+}
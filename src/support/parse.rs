@@ -0,0 +1,245 @@
+use super::H256;
+
+/// Implemented by the arguments of calls, to parse calls written like
+/// `balances.transfer(to="bob", amount=10)`. This is the reverse of `support::DisplayArg`.
+pub trait ParseArg: Sized {
+	/// Parse the argument from its text, without the surrounding whitespace.
+	fn parse_arg(s: &str) -> Result<Self, String>;
+}
+
+macro_rules! parse_arg_from_str {
+	($($t:ty),*) => {
+		$(
+			impl ParseArg for $t {
+				fn parse_arg(s: &str) -> Result<Self, String> {
+					s.parse().map_err(|_| format!("expected {}, found `{}`", stringify!($t), s))
+				}
+			}
+		)*
+	};
+}
+
+parse_arg_from_str!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool);
+
+/// Whether `s` can be written as a string argument without quotes: it is not empty, and has no
+/// whitespace, quotes, brackets, parentheses, commas or `=`.
+pub fn is_bare_word(s: &str) -> bool {
+	!s.is_empty() && !s.contains(|c: char| c.is_whitespace() || "\"()[],=".contains(c))
+}
+
+/// A string is either quoted, where `\"` and `\\` are escaped, or written as a bare word.
+impl ParseArg for String {
+	fn parse_arg(s: &str) -> Result<Self, String> {
+		let Some(quoted) = s.strip_prefix('"') else {
+			if !is_bare_word(s) {
+				return Err(format!("expected a string, found `{}`", s));
+			}
+			return Ok(s.to_string());
+		};
+
+		let mut string = String::new();
+		let mut chars = quoted.chars();
+		while let Some(c) = chars.next() {
+			match c {
+				'\\' => match chars.next() {
+					Some(c @ ('"' | '\\')) => string.push(c),
+					_ => return Err(format!("invalid escape in string `{}`", s)),
+				},
+				'"' if chars.as_str().is_empty() => return Ok(string),
+				'"' => return Err(format!("unexpected `\"` in string `{}`", s)),
+				c => string.push(c),
+			}
+		}
+		Err(format!("unterminated string `{}`", s))
+	}
+}
+
+/// A hash is written as `0x` followed by 64 hex digits, like it is displayed.
+impl ParseArg for H256 {
+	fn parse_arg(s: &str) -> Result<Self, String> {
+		let error = || format!("expected a 0x-prefixed 32 byte hash, found `{}`", s);
+		let hex = s.strip_prefix("0x").ok_or_else(error)?;
+		if hex.len() != 64 || !hex.is_ascii() {
+			return Err(error());
+		}
+
+		let mut hash = [0u8; 32];
+		for (i, byte) in hash.iter_mut().enumerate() {
+			*byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| error())?;
+		}
+		Ok(H256(hash))
+	}
+}
+
+impl<T: ParseArg> ParseArg for Option<T> {
+	fn parse_arg(s: &str) -> Result<Self, String> {
+		if s == "None" {
+			return Ok(None);
+		}
+		let inner = s
+			.strip_prefix("Some(")
+			.and_then(|s| s.strip_suffix(')'))
+			.ok_or_else(|| format!("expected `None` or `Some(..)`, found `{}`", s))?;
+		T::parse_arg(inner.trim()).map(Some)
+	}
+}
+
+impl<T: ParseArg> ParseArg for Vec<T> {
+	fn parse_arg(s: &str) -> Result<Self, String> {
+		let inner = s
+			.strip_prefix('[')
+			.and_then(|s| s.strip_suffix(']'))
+			.ok_or_else(|| format!("expected a list like `[..]`, found `{}`", s))?;
+		split_top_level(inner, ',')?.into_iter().map(T::parse_arg).collect()
+	}
+}
+
+impl<A: ParseArg, B: ParseArg> ParseArg for (A, B) {
+	fn parse_arg(s: &str) -> Result<Self, String> {
+		let error = || format!("expected a pair like `(a, b)`, found `{}`", s);
+		let inner = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')).ok_or_else(error)?;
+		match split_top_level(inner, ',')?[..] {
+			[a, b] => Ok((A::parse_arg(a)?, B::parse_arg(b)?)),
+			_ => Err(error()),
+		}
+	}
+}
+
+/// The named arguments of a call being parsed, which are taken out one by one to build the call.
+pub struct CallArgs<'a> {
+	call: &'a str,
+	args: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> CallArgs<'a> {
+	/// Split a call like `transfer(to="bob", amount=10)` into its name and its arguments.
+	/// Arguments can also be written like `to: bob`, which is how calls are displayed.
+	pub fn parse(s: &'a str) -> Result<(&'a str, Self), String> {
+		let s = s.trim();
+		let (call, rest) = s
+			.split_once('(')
+			.ok_or_else(|| format!("expected a call like `name(..)`, found `{}`", s))?;
+		let call = call.trim();
+		let inner =
+			rest.strip_suffix(')').ok_or_else(|| format!("expected `)` at the end of `{}`", s))?;
+
+		let mut args = Vec::new();
+		for arg in split_top_level(inner, ',')? {
+			let (name, value) = arg.split_once(['=', ':']).ok_or_else(|| {
+				format!("expected an argument like `name=value` in `{}`, found `{}`", call, arg)
+			})?;
+			let name = name.trim();
+			if args.iter().any(|(other, _)| *other == name) {
+				return Err(format!("duplicate argument `{}` of `{}`", name, call));
+			}
+			args.push((name, value.trim()));
+		}
+		Ok((call, Self { call, args }))
+	}
+
+	/// Parse the argument `name`, which must have been given.
+	pub fn take<T: ParseArg>(&mut self, name: &str) -> Result<T, String> {
+		let position = self
+			.args
+			.iter()
+			.position(|(arg, _)| *arg == name)
+			.ok_or_else(|| format!("missing argument `{}` of `{}`", name, self.call))?;
+		let (_, value) = self.args.remove(position);
+		T::parse_arg(value)
+			.map_err(|e| format!("invalid argument `{}` of `{}`: {}", name, self.call, e))
+	}
+
+	/// Check that every argument was taken, so none of them is unknown.
+	pub fn finish(self) -> Result<(), String> {
+		match self.args.first() {
+			Some((name, _)) => Err(format!("unknown argument `{}` of `{}`", name, self.call)),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Split a runtime call like `balances.transfer(..)` into the name of its pallet and the call.
+pub fn split_pallet(s: &str) -> Result<(&str, &str), String> {
+	s.trim()
+		.split_once('.')
+		.filter(|(pallet, _)| !pallet.contains('('))
+		.map(|(pallet, call)| (pallet.trim(), call))
+		.ok_or_else(|| format!("expected a call like `pallet.name(..)`, found `{}`", s.trim()))
+}
+
+/// Split `s` at every `separator` which is not nested in brackets, parentheses or a string, and
+/// trim the parts. An empty `s` has no parts.
+pub fn split_top_level(s: &str, separator: char) -> Result<Vec<&str>, String> {
+	let mut parts = Vec::new();
+	let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+	let mut start = 0;
+	for (i, c) in s.char_indices() {
+		match c {
+			_ if escaped => escaped = false,
+			'\\' if in_string => escaped = true,
+			'"' => in_string = !in_string,
+			_ if in_string => {},
+			'(' | '[' => depth += 1,
+			')' | ']' => {
+				depth = depth.checked_sub(1).ok_or_else(|| format!("unbalanced `{}` in `{}`", c, s))?
+			},
+			c if c == separator && depth == 0 => {
+				parts.push(s[start..i].trim());
+				start = i + c.len_utf8();
+			},
+			_ => {},
+		}
+	}
+	if depth != 0 || in_string {
+		return Err(format!("unbalanced brackets or quotes in `{}`", s));
+	}
+
+	let last = s[start..].trim();
+	if !(last.is_empty() && parts.is_empty()) {
+		parts.push(last);
+	}
+	Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_args() {
+		assert_eq!(u128::parse_arg("10"), Ok(10));
+		assert_eq!(u8::parse_arg("256"), Err("expected u8, found `256`".to_string()));
+		assert_eq!(String::parse_arg("bob"), Ok("bob".to_string()));
+		assert_eq!(
+			String::parse_arg(r#""a \"quoted\", string""#),
+			Ok(r#"a "quoted", string"#.to_string())
+		);
+		assert_eq!(Option::<u32>::parse_arg("Some(3)"), Ok(Some(3)));
+		assert_eq!(
+			Vec::<String>::parse_arg(r#"[a, "b,c"]"#),
+			Ok(vec!["a".to_string(), "b,c".to_string()])
+		);
+		assert_eq!(Vec::<u32>::parse_arg("[]"), Ok(vec![]));
+		assert_eq!(<(u8, String)>::parse_arg("(1, \"a, b\")"), Ok((1, "a, b".to_string())));
+		assert!(<(u8, u8)>::parse_arg("(1, 2, 3)").is_err());
+
+		let hash = H256([0xab; 32]);
+		assert_eq!(H256::parse_arg(&hash.to_string()), Ok(hash));
+	}
+
+	#[test]
+	fn parse_call_args() {
+		let (call, mut args) = CallArgs::parse(r#"transfer(to="bob", amount=10, memo=x)"#).unwrap();
+		assert_eq!(call, "transfer");
+		assert_eq!(args.take::<String>("to"), Ok("bob".to_string()));
+		assert_eq!(args.take::<u128>("to"), Err("missing argument `to` of `transfer`".to_string()));
+		assert_eq!(
+			args.take::<u128>("memo"),
+			Err("invalid argument `memo` of `transfer`: expected u128, found `x`".to_string())
+		);
+		assert_eq!(args.finish(), Err("unknown argument `amount` of `transfer`".to_string()));
+
+		assert!(CallArgs::parse("transfer(to=bob, to=alice)").is_err());
+		assert!(CallArgs::parse("transfer(to=[bob)").is_err());
+	}
+}
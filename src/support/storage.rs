@@ -0,0 +1,807 @@
+use super::codec::{decode_all, Decode, Encode};
+use super::{BlakeTwo256, Hashing};
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+/// Access to storage through raw keys and encoded values, like a single key/value store.
+///
+/// The raw key of an entry is the hash of the prefix of its storage item, followed by the encoded
+/// keys of the entry, if the item is a map. This is implemented by the storage items, by pallets
+/// for all their items with `#[macros::storage]`, and by the runtime for all its pallets with
+/// `#[macros::runtime]`, which together make up the state of the runtime.
+pub trait RawStorage {
+	/// Check if `key` starts with the prefix of a storage item of this storage.
+	fn owns_key(&self, key: &[u8]) -> bool;
+
+	/// Get the encoded value stored at `key`, if any. The default value of an item is not stored.
+	fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Decode `value` and store it at `key`.
+	fn set_raw(&mut self, key: &[u8], value: &[u8]) -> Result<(), RawStorageError>;
+
+	/// Remove the value stored at `key`, if any.
+	fn kill_raw(&mut self, key: &[u8]) -> Result<(), RawStorageError>;
+}
+
+/// The reasons a raw key or value can be rejected by `RawStorage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawStorageError {
+	/// No storage item has a prefix matching the key.
+	UnknownKey,
+	/// The key matches the prefix of an item, but the rest of it is not a valid key of the item.
+	InvalidKey,
+	/// The value is not a valid value of the item.
+	InvalidValue,
+}
+
+/// Storage whose changes can be made in nested transactions, which are either committed or rolled
+/// back as a whole.
+///
+/// The storage items keep a layer of changes for each open transaction, on top of their committed
+/// values. Reads look through the layers from the most recent one, and writes only go to the most
+/// recent layer. Pallets implement this for all their items with `#[macros::storage]`, and the
+/// runtime for all its pallets with `#[macros::runtime]`.
+pub trait Transactional {
+	/// Open a new transaction, nested in the transaction which is already open, if any.
+	fn start_transaction(&mut self);
+
+	/// Keep the changes of the most recent transaction, as part of the transaction around it.
+	fn commit_transaction(&mut self);
+
+	/// Discard the changes of the most recent transaction.
+	fn rollback_transaction(&mut self);
+}
+
+/// Run `f` in a new transaction of `state`, which is committed if `f` succeeds, and rolled back
+/// if it fails.
+pub fn with_transaction<S: Transactional, R, E>(
+	state: &mut S,
+	f: impl FnOnce(&mut S) -> Result<R, E>,
+) -> Result<R, E> {
+	state.start_transaction();
+	let result = f(state);
+	match result {
+		Ok(_) => state.commit_transaction(),
+		Err(_) => state.rollback_transaction(),
+	}
+	result
+}
+
+/// The raw key of the storage item with `prefix`, which starts the raw keys of all its entries.
+pub fn prefix_key(prefix: &str) -> Vec<u8> {
+	BlakeTwo256::hash(prefix.as_bytes()).0.to_vec()
+}
+
+/// The part of `key` after the prefix of the item with `prefix`, if `key` starts with it.
+fn strip_prefix_key<'a>(prefix: &str, key: &'a [u8]) -> Option<&'a [u8]> {
+	key.strip_prefix(prefix_key(prefix).as_slice())
+}
+
+/// What reading a storage item returns when nothing is stored, given as the last type argument of
+/// the item.
+pub trait QueryKind {
+	/// The type returned by reading an item with values of type `V`.
+	type Query<V>;
+	/// The default value an item with values of type `V` is created with.
+	type Default<V>;
+
+	/// The result of a read, from the stored value, if any.
+	fn from_stored<V: Clone>(stored: Option<&V>, default: &Self::Default<V>) -> Self::Query<V>;
+
+	/// The value to store for `query`, where `None` removes the stored value.
+	fn into_stored<V>(query: Self::Query<V>) -> Option<V>;
+}
+
+/// Reading an item where nothing is stored returns its default value. This is the default kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueQuery;
+
+impl QueryKind for ValueQuery {
+	type Query<V> = V;
+	type Default<V> = V;
+
+	fn from_stored<V: Clone>(stored: Option<&V>, default: &V) -> V {
+		stored.unwrap_or(default).clone()
+	}
+
+	fn into_stored<V>(query: V) -> Option<V> {
+		Some(query)
+	}
+}
+
+/// Reading an item where nothing is stored returns `None`, so the item has no default value, and
+/// writing `None` removes the stored value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionQuery;
+
+impl QueryKind for OptionQuery {
+	type Query<V> = Option<V>;
+	type Default<V> = ();
+
+	fn from_stored<V: Clone>(stored: Option<&V>, _default: &()) -> Option<V> {
+		stored.cloned()
+	}
+
+	fn into_stored<V>(query: Option<V>) -> Option<V> {
+		query
+	}
+}
+
+/// A single value in the storage of a pallet, declared with `#[macros::storage]`.
+///
+/// Reading the value returns what `Q` gives when it is not set, which is its default for
+/// `ValueQuery`, and `None` for `OptionQuery`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageValue<V, Q: QueryKind = ValueQuery> {
+	prefix: String,
+	default: Q::Default<V>,
+	value: Option<V>,
+	/// The change made by each open transaction, if any, where `Some(None)` removes the value.
+	layers: Vec<Option<Option<V>>>,
+}
+
+impl<V: Clone, Q: QueryKind> StorageValue<V, Q> {
+	/// Create an empty storage value, which is unique in the runtime by its `prefix`.
+	pub fn new(prefix: impl Into<String>, default: Q::Default<V>) -> Self {
+		Self { prefix: prefix.into(), default, value: None, layers: Vec::new() }
+	}
+
+	/// The value as seen by the most recent transaction, if it is set.
+	fn current(&self) -> Option<&V> {
+		match self.layers.iter().rev().find_map(Option::as_ref) {
+			Some(change) => change.as_ref(),
+			None => self.value.as_ref(),
+		}
+	}
+
+	/// Write `value` in the most recent transaction, or directly if there is none.
+	fn write(&mut self, value: Option<V>) {
+		match self.layers.last_mut() {
+			Some(layer) => *layer = Some(value),
+			None => self.value = value,
+		}
+	}
+
+	/// The prefix which identifies this storage item in the runtime.
+	pub fn prefix(&self) -> &str {
+		&self.prefix
+	}
+
+	/// Get the value, or what `Q` gives if it was never set.
+	pub fn get(&self) -> Q::Query<V> {
+		Q::from_stored(self.current(), &self.default)
+	}
+
+	/// Check if the value was set.
+	pub fn exists(&self) -> bool {
+		self.current().is_some()
+	}
+
+	/// Set the value.
+	pub fn put(&mut self, value: V) {
+		self.write(Some(value));
+	}
+
+	/// Remove the value, so it is back to its default.
+	pub fn kill(&mut self) {
+		self.write(None);
+	}
+
+	/// Set the value as it is read, where `None` removes it for `OptionQuery`.
+	pub fn set(&mut self, value: Q::Query<V>) {
+		self.write(Q::into_stored(value));
+	}
+
+	/// Update the value in place, starting from what `get` returns.
+	pub fn mutate<R>(&mut self, f: impl FnOnce(&mut Q::Query<V>) -> R) -> R {
+		let mut value = self.get();
+		let result = f(&mut value);
+		self.set(value);
+		result
+	}
+
+	/// The raw key of the value, as used by `RawStorage`.
+	pub fn raw_key(&self) -> Vec<u8> {
+		prefix_key(&self.prefix)
+	}
+}
+
+impl<V: Clone + Encode + Decode, Q: QueryKind> RawStorage for StorageValue<V, Q> {
+	fn owns_key(&self, key: &[u8]) -> bool {
+		strip_prefix_key(&self.prefix, key).is_some()
+	}
+
+	fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>> {
+		match strip_prefix_key(&self.prefix, key)? {
+			[] => self.current().map(Encode::encode),
+			_ => None,
+		}
+	}
+
+	fn set_raw(&mut self, key: &[u8], value: &[u8]) -> Result<(), RawStorageError> {
+		let key = strip_prefix_key(&self.prefix, key).ok_or(RawStorageError::UnknownKey)?;
+		if !key.is_empty() {
+			return Err(RawStorageError::InvalidKey);
+		}
+		self.put(decode_all(value).ok_or(RawStorageError::InvalidValue)?);
+		Ok(())
+	}
+
+	fn kill_raw(&mut self, key: &[u8]) -> Result<(), RawStorageError> {
+		let key = strip_prefix_key(&self.prefix, key).ok_or(RawStorageError::UnknownKey)?;
+		if !key.is_empty() {
+			return Err(RawStorageError::InvalidKey);
+		}
+		self.kill();
+		Ok(())
+	}
+}
+
+impl<V: Clone, Q: QueryKind> Transactional for StorageValue<V, Q> {
+	fn start_transaction(&mut self) {
+		self.layers.push(None);
+	}
+
+	fn commit_transaction(&mut self) {
+		if let Some(Some(change)) = self.layers.pop() {
+			self.write(change);
+		}
+	}
+
+	fn rollback_transaction(&mut self) {
+		self.layers.pop();
+	}
+}
+
+/// The entries of a map, with the changes made to them by each open transaction, where a `None`
+/// change removes the entry. This is shared by `StorageMap` and `StorageDoubleMap`.
+#[derive(Debug, Clone, PartialEq)]
+struct LayeredMap<K, V> {
+	map: BTreeMap<K, V>,
+	layers: Vec<BTreeMap<K, Option<V>>>,
+	/// The number of entries as seen by the most recent transaction.
+	len: usize,
+	/// The number of entries when each open transaction started, which is restored when it is
+	/// rolled back.
+	lens: Vec<usize>,
+}
+
+impl<K: Ord + Clone, V: Clone> LayeredMap<K, V> {
+	fn new() -> Self {
+		Self { map: BTreeMap::new(), layers: Vec::new(), len: 0, lens: Vec::new() }
+	}
+
+	/// The value of `key` as seen by the most recent transaction.
+	fn get(&self, key: &K) -> Option<&V> {
+		match self.layers.iter().rev().find_map(|layer| layer.get(key)) {
+			Some(change) => change.as_ref(),
+			None => self.map.get(key),
+		}
+	}
+
+	/// Write `value` for `key` in the most recent transaction, or directly if there is none.
+	fn write(&mut self, key: K, value: Option<V>) {
+		match (self.get(&key).is_some(), value.is_some()) {
+			(false, true) => self.len += 1,
+			(true, false) => self.len -= 1,
+			_ => {},
+		}
+		self.store(key, value);
+	}
+
+	/// Store `value` for `key` in the most recent layer, without counting the entries.
+	fn store(&mut self, key: K, value: Option<V>) {
+		match (self.layers.last_mut(), value) {
+			(Some(layer), value) => {
+				layer.insert(key, value);
+			},
+			(None, Some(value)) => {
+				self.map.insert(key, value);
+			},
+			(None, None) => {
+				self.map.remove(&key);
+			},
+		}
+	}
+
+	/// Remove `key`, returning its value if it was in the map.
+	fn remove(&mut self, key: &K) -> Option<V> {
+		let value = self.get(key).cloned();
+		self.write(key.clone(), None);
+		value
+	}
+
+	/// The number of entries as seen by the most recent transaction.
+	fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Iterate over the entries whose keys are in `range` as seen by the most recent transaction,
+	/// in the order of their keys. The changes of the open transactions, if any, are merged into a
+	/// new map, while the committed entries are iterated over directly.
+	fn range<R: RangeBounds<K> + Clone>(&self, range: R) -> impl Iterator<Item = (&K, &V)> {
+		let committed = self.layers.is_empty().then(|| self.map.range(range.clone()));
+		let merged = (!self.layers.is_empty()).then(|| {
+			let mut entries = self.map.range(range.clone()).collect::<BTreeMap<_, _>>();
+			for layer in &self.layers {
+				for (key, change) in layer.range(range.clone()) {
+					match change {
+						Some(value) => entries.insert(key, value),
+						None => entries.remove(key),
+					};
+				}
+			}
+			entries.into_iter()
+		});
+		committed.into_iter().flatten().chain(merged.into_iter().flatten())
+	}
+
+	/// Remove all the entries whose keys are in `range`.
+	fn remove_range<R: RangeBounds<K> + Clone>(&mut self, range: R) {
+		let keys = self.range(range).map(|(key, _)| key.clone()).collect::<Vec<_>>();
+		for key in keys {
+			self.write(key, None);
+		}
+	}
+}
+
+impl<K: Ord + Clone, V: Clone> Transactional for LayeredMap<K, V> {
+	fn start_transaction(&mut self) {
+		self.layers.push(BTreeMap::new());
+		self.lens.push(self.len);
+	}
+
+	fn commit_transaction(&mut self) {
+		self.lens.pop();
+		for (key, change) in self.layers.pop().unwrap_or_default() {
+			self.store(key, change);
+		}
+	}
+
+	fn rollback_transaction(&mut self) {
+		self.layers.pop();
+		if let Some(len) = self.lens.pop() {
+			self.len = len;
+		}
+	}
+}
+
+/// The second key of the entries of a `StorageDoubleMap`, which can also be a bound sorting before
+/// or after all the keys, so that the entries sharing a first key are a range of the map.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SecondKey<K> {
+	Start,
+	Key(K),
+	End,
+}
+
+impl<K> SecondKey<K> {
+	/// The key of a stored entry, which is never a bound.
+	fn key(&self) -> &K {
+		match self {
+			SecondKey::Key(key) => key,
+			SecondKey::Start | SecondKey::End => unreachable!("only keys are stored"),
+		}
+	}
+}
+
+/// A map from keys to values in the storage of a pallet, declared with `#[macros::storage]`.
+///
+/// Reading a key which is not in the map returns what `Q` gives, which is the default value for
+/// `ValueQuery`, and `None` for `OptionQuery`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageMap<K, V, Q: QueryKind = ValueQuery> {
+	prefix: String,
+	default: Q::Default<V>,
+	map: LayeredMap<K, V>,
+}
+
+impl<K: Ord + Clone, V: Clone, Q: QueryKind> StorageMap<K, V, Q> {
+	/// Create an empty storage map, which is unique in the runtime by its `prefix`.
+	pub fn new(prefix: impl Into<String>, default: Q::Default<V>) -> Self {
+		Self { prefix: prefix.into(), default, map: LayeredMap::new() }
+	}
+
+	/// The prefix which identifies this storage item in the runtime.
+	pub fn prefix(&self) -> &str {
+		&self.prefix
+	}
+
+	/// Get the value of `key`, or what `Q` gives if it is not in the map.
+	pub fn get(&self, key: &K) -> Q::Query<V> {
+		Q::from_stored(self.map.get(key), &self.default)
+	}
+
+	/// Get a reference to the value of `key`, if it is in the map. Unlike `get`, this does not
+	/// fall back to the default value.
+	pub fn get_ref(&self, key: &K) -> Option<&V> {
+		self.map.get(key)
+	}
+
+	/// Check if `key` is in the map.
+	pub fn contains_key(&self, key: &K) -> bool {
+		self.map.get(key).is_some()
+	}
+
+	/// Set the value of `key`.
+	pub fn insert(&mut self, key: K, value: V) {
+		self.map.write(key, Some(value));
+	}
+
+	/// Remove `key` from the map, returning its value if it was in the map.
+	pub fn remove(&mut self, key: &K) -> Option<V> {
+		self.map.remove(key)
+	}
+
+	/// Set the value of `key` as it is read, where `None` removes it for `OptionQuery`.
+	pub fn set(&mut self, key: K, value: Q::Query<V>) {
+		self.map.write(key, Q::into_stored(value));
+	}
+
+	/// Update the value of `key` in place, starting from what `get` returns.
+	pub fn mutate<R>(&mut self, key: K, f: impl FnOnce(&mut Q::Query<V>) -> R) -> R {
+		let mut value = self.get(&key);
+		let result = f(&mut value);
+		self.set(key, value);
+		result
+	}
+
+	/// Iterate over the entries of the map, in the order of their keys.
+	pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.map.range(..)
+	}
+
+	/// The number of entries in the map.
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Check if the map has no entries.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Remove all the entries of the map.
+	pub fn clear(&mut self) {
+		self.map.remove_range(..);
+	}
+
+	/// The raw key of the entry of `key`, as used by `RawStorage`.
+	pub fn raw_key(&self, key: &K) -> Vec<u8>
+	where
+		K: Encode,
+	{
+		let mut raw_key = prefix_key(&self.prefix);
+		key.encode_to(&mut raw_key);
+		raw_key
+	}
+}
+
+impl<K, V, Q> RawStorage for StorageMap<K, V, Q>
+where
+	K: Ord + Clone + Encode + Decode,
+	V: Clone + Encode + Decode,
+	Q: QueryKind,
+{
+	fn owns_key(&self, key: &[u8]) -> bool {
+		strip_prefix_key(&self.prefix, key).is_some()
+	}
+
+	fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>> {
+		let key = decode_all::<K>(strip_prefix_key(&self.prefix, key)?)?;
+		self.map.get(&key).map(Encode::encode)
+	}
+
+	fn set_raw(&mut self, key: &[u8], value: &[u8]) -> Result<(), RawStorageError> {
+		let key = strip_prefix_key(&self.prefix, key).ok_or(RawStorageError::UnknownKey)?;
+		let key = decode_all(key).ok_or(RawStorageError::InvalidKey)?;
+		self.insert(key, decode_all(value).ok_or(RawStorageError::InvalidValue)?);
+		Ok(())
+	}
+
+	fn kill_raw(&mut self, key: &[u8]) -> Result<(), RawStorageError> {
+		let key = strip_prefix_key(&self.prefix, key).ok_or(RawStorageError::UnknownKey)?;
+		self.remove(&decode_all(key).ok_or(RawStorageError::InvalidKey)?);
+		Ok(())
+	}
+}
+
+impl<K: Ord + Clone, V: Clone, Q: QueryKind> Transactional for StorageMap<K, V, Q> {
+	fn start_transaction(&mut self) {
+		self.map.start_transaction();
+	}
+
+	fn commit_transaction(&mut self) {
+		self.map.commit_transaction();
+	}
+
+	fn rollback_transaction(&mut self) {
+		self.map.rollback_transaction();
+	}
+}
+
+/// A map from pairs of keys to values in the storage of a pallet, declared with
+/// `#[macros::storage]`. All the entries sharing the same first key can be iterated over.
+///
+/// Reading a pair of keys which is not in the map returns what `Q` gives, like `StorageMap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageDoubleMap<K1, K2, V, Q: QueryKind = ValueQuery> {
+	prefix: String,
+	default: Q::Default<V>,
+	map: LayeredMap<(K1, SecondKey<K2>), V>,
+}
+
+impl<K1: Ord + Clone, K2: Ord + Clone, V: Clone, Q: QueryKind> StorageDoubleMap<K1, K2, V, Q> {
+	/// Create an empty storage double map, which is unique in the runtime by its `prefix`.
+	pub fn new(prefix: impl Into<String>, default: Q::Default<V>) -> Self {
+		Self { prefix: prefix.into(), default, map: LayeredMap::new() }
+	}
+
+	/// The prefix which identifies this storage item in the runtime.
+	pub fn prefix(&self) -> &str {
+		&self.prefix
+	}
+
+	/// The key of the entry of `(key1, key2)` in the underlying map.
+	fn key(key1: &K1, key2: &K2) -> (K1, SecondKey<K2>) {
+		(key1.clone(), SecondKey::Key(key2.clone()))
+	}
+
+	/// The keys of all the entries whose first key is `key1` in the underlying map.
+	fn prefix_range(key1: &K1) -> impl RangeBounds<(K1, SecondKey<K2>)> + Clone {
+		(
+			Bound::Excluded((key1.clone(), SecondKey::Start)),
+			Bound::Excluded((key1.clone(), SecondKey::End)),
+		)
+	}
+
+	/// Get the value of `(key1, key2)`, or what `Q` gives if it is not in the map.
+	pub fn get(&self, key1: &K1, key2: &K2) -> Q::Query<V> {
+		Q::from_stored(self.map.get(&Self::key(key1, key2)), &self.default)
+	}
+
+	/// Check if `(key1, key2)` is in the map.
+	pub fn contains_key(&self, key1: &K1, key2: &K2) -> bool {
+		self.map.get(&Self::key(key1, key2)).is_some()
+	}
+
+	/// Set the value of `(key1, key2)`.
+	pub fn insert(&mut self, key1: K1, key2: K2, value: V) {
+		self.map.write((key1, SecondKey::Key(key2)), Some(value));
+	}
+
+	/// Remove `(key1, key2)` from the map, returning its value if it was in the map.
+	pub fn remove(&mut self, key1: &K1, key2: &K2) -> Option<V> {
+		self.map.remove(&Self::key(key1, key2))
+	}
+
+	/// Set the value of `(key1, key2)` as it is read, where `None` removes it for `OptionQuery`.
+	pub fn set(&mut self, key1: K1, key2: K2, value: Q::Query<V>) {
+		self.map.write((key1, SecondKey::Key(key2)), Q::into_stored(value));
+	}
+
+	/// Update the value of `(key1, key2)` in place, starting from what `get` returns.
+	pub fn mutate<R>(&mut self, key1: K1, key2: K2, f: impl FnOnce(&mut Q::Query<V>) -> R) -> R {
+		let mut value = self.get(&key1, &key2);
+		let result = f(&mut value);
+		self.set(key1, key2, value);
+		result
+	}
+
+	/// Iterate over the entries of the map, in the order of their keys.
+	pub fn iter(&self) -> impl Iterator<Item = (&K1, &K2, &V)> {
+		self.map.range(..).map(|((key1, key2), value)| (key1, key2.key(), value))
+	}
+
+	/// Iterate over the entries whose first key is `key1`, in the order of their second key.
+	pub fn iter_prefix(&self, key1: &K1) -> impl Iterator<Item = (&K2, &V)> {
+		self.map.range(Self::prefix_range(key1)).map(|((_, key2), value)| (key2.key(), value))
+	}
+
+	/// Remove all the entries whose first key is `key1`.
+	pub fn remove_prefix(&mut self, key1: &K1) {
+		self.map.remove_range(Self::prefix_range(key1));
+	}
+
+	/// The number of entries in the map.
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Check if the map has no entries.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The raw key of the entry of `(key1, key2)`, as used by `RawStorage`.
+	pub fn raw_key(&self, key1: &K1, key2: &K2) -> Vec<u8>
+	where
+		K1: Encode,
+		K2: Encode,
+	{
+		let mut raw_key = prefix_key(&self.prefix);
+		key1.encode_to(&mut raw_key);
+		key2.encode_to(&mut raw_key);
+		raw_key
+	}
+}
+
+impl<K1, K2, V, Q> RawStorage for StorageDoubleMap<K1, K2, V, Q>
+where
+	K1: Ord + Clone + Encode + Decode,
+	K2: Ord + Clone + Encode + Decode,
+	V: Clone + Encode + Decode,
+	Q: QueryKind,
+{
+	fn owns_key(&self, key: &[u8]) -> bool {
+		strip_prefix_key(&self.prefix, key).is_some()
+	}
+
+	fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>> {
+		let (key1, key2) = decode_all::<(K1, K2)>(strip_prefix_key(&self.prefix, key)?)?;
+		self.map.get(&Self::key(&key1, &key2)).map(Encode::encode)
+	}
+
+	fn set_raw(&mut self, key: &[u8], value: &[u8]) -> Result<(), RawStorageError> {
+		let key = strip_prefix_key(&self.prefix, key).ok_or(RawStorageError::UnknownKey)?;
+		let (key1, key2) = decode_all(key).ok_or(RawStorageError::InvalidKey)?;
+		self.insert(key1, key2, decode_all(value).ok_or(RawStorageError::InvalidValue)?);
+		Ok(())
+	}
+
+	fn kill_raw(&mut self, key: &[u8]) -> Result<(), RawStorageError> {
+		let key = strip_prefix_key(&self.prefix, key).ok_or(RawStorageError::UnknownKey)?;
+		let (key1, key2) = decode_all(key).ok_or(RawStorageError::InvalidKey)?;
+		self.remove(&key1, &key2);
+		Ok(())
+	}
+}
+
+impl<K1, K2, V, Q> Transactional for StorageDoubleMap<K1, K2, V, Q>
+where
+	K1: Ord + Clone,
+	K2: Ord + Clone,
+	V: Clone,
+	Q: QueryKind,
+{
+	fn start_transaction(&mut self) {
+		self.map.start_transaction();
+	}
+
+	fn commit_transaction(&mut self) {
+		self.map.commit_transaction();
+	}
+
+	fn rollback_transaction(&mut self) {
+		self.map.rollback_transaction();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn storage_value() {
+		let mut value: StorageValue<u32> = StorageValue::new("test::value", 7);
+		assert_eq!((value.get(), value.exists()), (7, false));
+
+		value.put(1);
+		assert_eq!(value.mutate(|v| { *v += 1; *v }), 2);
+		assert_eq!(value.get(), 2);
+
+		value.kill();
+		assert_eq!((value.get(), value.exists()), (7, false));
+		assert_eq!(value.prefix(), "test::value");
+	}
+
+	#[test]
+	fn storage_map() {
+		let mut map: StorageMap<_, u32> = StorageMap::new("test::map", 0);
+		assert_eq!(map.get(&"alice"), 0);
+
+		map.insert("bob", 2);
+		map.mutate("alice", |v| *v += 1);
+		assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"alice", &1), (&"bob", &2)]);
+
+		assert_eq!(map.remove(&"bob"), Some(2));
+		assert!(!map.contains_key(&"bob"));
+		assert_eq!(map.len(), 1);
+	}
+
+	#[test]
+	fn option_query() {
+		let mut map: StorageMap<_, u32, OptionQuery> = StorageMap::new("test::map", ());
+		assert_eq!(map.get(&"alice"), None);
+
+		map.set("alice", Some(1));
+		map.mutate("alice", |v| *v = v.map(|v| v + 1));
+		assert_eq!(map.get(&"alice"), Some(2));
+		assert_eq!(map.get_ref(&"alice"), Some(&2));
+
+		map.mutate("alice", |v| *v = None);
+		assert!(!map.contains_key(&"alice"));
+	}
+
+	#[test]
+	fn storage_double_map() {
+		let mut map: StorageDoubleMap<_, _, u32> = StorageDoubleMap::new("test::double_map", 0);
+		map.insert(1, "alice", 10);
+		map.insert(2, "alice", 20);
+		map.insert(2, "bob", 30);
+		map.insert(3, "alice", 40);
+
+		assert_eq!(map.get(&2, &"bob"), 30);
+		assert_eq!(map.get(&1, &"bob"), 0);
+		assert_eq!(map.iter_prefix(&2).collect::<Vec<_>>(), vec![(&"alice", &20), (&"bob", &30)]);
+
+		map.remove_prefix(&2);
+		assert_eq!(map.iter().map(|(k1, _, _)| *k1).collect::<Vec<_>>(), vec![1, 3]);
+
+		// The changes of an open transaction are seen by prefix, and counted.
+		map.start_transaction();
+		map.insert(3, "bob", 50);
+		map.remove(&1, &"alice");
+		assert_eq!(map.iter_prefix(&3).collect::<Vec<_>>(), vec![(&"alice", &40), (&"bob", &50)]);
+		assert_eq!(map.len(), 2);
+		map.rollback_transaction();
+		assert_eq!(map.iter_prefix(&3).collect::<Vec<_>>(), vec![(&"alice", &40)]);
+		assert_eq!(map.len(), 2);
+	}
+
+	#[test]
+	fn transactions() {
+		let mut value: StorageValue<u32> = StorageValue::new("test::value", 0);
+		let mut map: StorageMap<_, u32> = StorageMap::new("test::map", 0);
+		map.insert("alice", 1);
+
+		value.start_transaction();
+		map.start_transaction();
+		value.put(1);
+		map.insert("bob", 2);
+		map.remove(&"alice");
+		assert_eq!((value.get(), map.get(&"alice"), map.get(&"bob")), (1, 0, 2));
+
+		// A nested transaction sees the changes of the one around it, and is rolled back alone.
+		map.start_transaction();
+		map.mutate("bob", |v| *v += 1);
+		map.clear();
+		assert!(map.is_empty());
+		map.rollback_transaction();
+		assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"bob", &2)]);
+		assert_eq!(map.len(), 1);
+
+		map.commit_transaction();
+		value.rollback_transaction();
+		assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"bob", &2)]);
+		assert_eq!((value.get(), value.exists()), (0, false));
+
+		let result = with_transaction(&mut map, |map| {
+			map.insert("carol", 3);
+			Err::<(), _>("failed")
+		});
+		assert_eq!(result, Err("failed"));
+		assert!(!map.contains_key(&"carol"));
+	}
+
+	#[test]
+	fn raw_storage() {
+		let mut map: StorageMap<_, u32> = StorageMap::new("test::map", 0);
+		let key = map.raw_key(&"alice".to_string());
+		assert!(map.owns_key(&key));
+		assert_eq!(map.get_raw(&key), None);
+
+		assert_eq!(map.set_raw(&key, &5u32.encode()), Ok(()));
+		assert_eq!(map.get(&"alice".to_string()), 5);
+		assert_eq!(map.get_raw(&key), Some(5u32.encode()));
+
+		let other = StorageMap::<String, u32>::new("test::other", 0).raw_key(&"alice".to_string());
+		assert!(!map.owns_key(&other));
+		assert_eq!(map.set_raw(&other, &5u32.encode()), Err(RawStorageError::UnknownKey));
+		assert_eq!(map.set_raw(&prefix_key("test::map"), &[]), Err(RawStorageError::InvalidKey));
+		assert_eq!(map.set_raw(&key, &[1]), Err(RawStorageError::InvalidValue));
+
+		assert_eq!(map.kill_raw(&key), Ok(()));
+		assert!(!map.contains_key(&"alice".to_string()));
+	}
+}
@@ -1,20 +1,164 @@
-use std::{collections::BTreeMap, ops::AddAssign};
-use num::traits::{Zero, One};
+use crate::support::{
+	storage::{
+		with_transaction, OptionQuery, RawStorage, RawStorageError, StorageMap, StorageValue,
+		Transactional,
+	},
+	BlakeTwo256, DispatchResult, ExecutionMode, Hashing, Moment, TakeEvents, Weight, DEFAULT_WEIGHT,
+	H256,
+};
+use std::{fmt::Debug, ops::AddAssign};
+use num::traits::{CheckedAdd, One, Zero};
 
 pub trait Config {
-	type AccountId: Ord + Clone;
-	type BlockNumber: Zero + One + AddAssign + Copy;
+	type AccountId: Ord + Clone + Debug;
+	type BlockNumber: Zero + One + AddAssign + CheckedAdd + Copy + Ord + Debug;
 	type Nonce: Zero + One + Copy;
+	/// The aggregated event type of the runtime, which the events of every pallet are
+	/// converted into.
+	type RuntimeEvent;
+
+	/// How blocks are executed when some of their extrinsics fail.
+	const EXECUTION_MODE: ExecutionMode = ExecutionMode::Lenient;
+
+	/// The account allowed to make the root calls `set_storage` and `kill_storage`, if any.
+	fn root() -> Option<Self::AccountId> {
+		None
+	}
+}
+
+/// The events emitted by the system pallet.
+#[derive(Debug, Clone, PartialEq, macros::EventMetadata)]
+pub enum Event<T: Config> {
+	/// `sender` made a remark, whose content has the given `hash`.
+	Remarked { sender: T::AccountId, hash: H256 },
+}
+
+/// The errors returned by the system pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+pub enum Error {
+	/// Only the root account can do this.
+	#[error("Caller is not root")]
+	NotRoot,
+	/// No storage item matches the key.
+	#[error("Unknown storage key")]
+	UnknownKey,
+	/// The key is not a valid key of the storage item it matches.
+	#[error("Invalid storage key")]
+	InvalidKey,
+	/// The value is not a valid value of the storage item.
+	#[error("Invalid storage value")]
+	InvalidValue,
+}
+
+impl From<RawStorageError> for Error {
+	fn from(error: RawStorageError) -> Self {
+		match error {
+			RawStorageError::UnknownKey => Error::UnknownKey,
+			RawStorageError::InvalidKey => Error::InvalidKey,
+			RawStorageError::InvalidValue => Error::InvalidValue,
+		}
+	}
 }
 
-#[derive(Debug)]
+#[macros::storage]
+#[derive(Debug, Clone, PartialEq)]
 
 /// This is the System Pallet.
 /// It handles low level state needed for your blockchain.
 pub struct Pallet<T:Config> 
 	where{
-	block_number: T::BlockNumber,
-    nonce: BTreeMap<T::AccountId,T::Nonce>,
+	/// Get the current block number.
+	#[storage(getter = block_number, default = T::BlockNumber::zero())]
+	block_number: StorageValue<T::BlockNumber>,
+	/// Get the time at which the current block was produced, as set in its header, if any.
+	#[storage(getter = now, setter = set_now)]
+	now: StorageValue<Moment, OptionQuery>,
+	/// Get the nonce of an account.
+	#[storage(getter = get_nonce, default = T::Nonce::zero())]
+    nonce: StorageMap<T::AccountId,T::Nonce>,
+	/// The events emitted by the pallets during the current block.
+	events: Vec<T::RuntimeEvent>,
+	/// Get how blocks are executed when some of their extrinsics fail.
+	/// It can be changed with `set_execution_mode`, overriding the `EXECUTION_MODE` of the runtime.
+	#[storage(getter = execution_mode, setter = set_execution_mode, default = T::EXECUTION_MODE)]
+	execution_mode: StorageValue<ExecutionMode>,
+	/// The events emitted by the system pallet itself since the runtime last collected them.
+	pending_events: Vec<Event<T>>,
+	/// The number of events collected when each open transaction started.
+	event_marks: Vec<usize>,
+}
+
+#[macros::call(explicit_call_index, error = Error)]
+impl<T: Config> Pallet<T>
+where
+	T: RawStorage + Transactional,
+{
+	/// Make a remark on chain. Nothing is stored.
+	#[call_index(0)]
+	#[weight(Pallet::<T>::remark_weight(remark))]
+	pub fn remark(&mut self, _caller: T::AccountId, _remark: Vec<u8>) -> DispatchResult {
+		Ok(())
+	}
+
+	/// Make a remark on chain, and emit a `Remarked` event with the hash of its content.
+	#[call_index(1)]
+	#[weight(Pallet::<T>::remark_weight(remark))]
+	pub fn remark_with_event(&mut self, caller: T::AccountId, remark: Vec<u8>) -> DispatchResult {
+		let hash = BlakeTwo256::hash(&remark);
+		self.pending_events.push(Event::Remarked { sender: caller, hash });
+		Ok(())
+	}
+
+	/// Set encoded values in the storage of the runtime, at the given raw keys. Only the root
+	/// account can call this, and either all of the values are set or none of them.
+	#[call_index(2)]
+	pub fn set_storage(
+		runtime: &mut T,
+		caller: T::AccountId,
+		items: Vec<(Vec<u8>, Vec<u8>)>,
+	) -> DispatchResult {
+		Self::ensure_root(&caller)?;
+		with_transaction(runtime, |runtime| {
+			items.iter().try_for_each(|(key, value)| runtime.set_raw(key, value))
+		})
+		.map_err(|error| Error::from(error).into())
+	}
+
+	/// Remove values from the storage of the runtime, at the given raw keys. Only the root account
+	/// can call this, and either all of the values are removed or none of them.
+	#[call_index(3)]
+	pub fn kill_storage(
+		runtime: &mut T,
+		caller: T::AccountId,
+		keys: Vec<Vec<u8>>,
+	) -> DispatchResult {
+		Self::ensure_root(&caller)?;
+		with_transaction(runtime, |runtime| keys.iter().try_for_each(|key| runtime.kill_raw(key)))
+			.map_err(|error| Error::from(error).into())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The weight of a remark grows with its length.
+	pub fn remark_weight(remark: &[u8]) -> Weight {
+		DEFAULT_WEIGHT.saturating_add(remark.len() as Weight)
+	}
+
+	/// Check that `who` is the root account of the runtime.
+	fn ensure_root(who: &T::AccountId) -> DispatchResult {
+		if T::root().as_ref() != Some(who) {
+			return Err(Error::NotRoot.into());
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> TakeEvents for Pallet<T> {
+	type Event = Event<T>;
+
+	fn take_events(&mut self) -> Vec<Event<T>> {
+		core::mem::take(&mut self.pending_events)
+	}
 }
 
 impl <T:Config>Pallet<T> 
@@ -23,37 +167,60 @@ impl <T:Config>Pallet<T>
 	T::BlockNumber: Zero + One + Copy + AddAssign,
 	T::Nonce: Zero + One + Copy,
 	{
-	/// Create a new instance of the System Pallet.
-	pub fn new() -> Self {
-		Self{
-            block_number: T::BlockNumber::zero(),
-            nonce: BTreeMap::new()
-        }
-	}
-
-    /// Get the current block number.
-	pub fn block_number(&self) -> T::BlockNumber {
-		self.block_number
-
-	}
-
 	// This function can be used to increment the block number.
 	// Increases the block number by one.
 	pub fn inc_block_number(&mut self) {
         //crashes if overflows
-		self.block_number += T::BlockNumber::one();
+		self.block_number.mutate(|block_number| *block_number += T::BlockNumber::one());
+	}
+
+	/// The number of the block which follows the current one, if it does not overflow.
+	pub fn next_block_number(&self) -> Option<T::BlockNumber> {
+		self.block_number().checked_add(&T::BlockNumber::one())
 	}
 
 	// Increment the nonce of an account. This helps us keep track of how many transactions each
 	// account has made.
 	pub fn inc_nonce(&mut self, who: &T::AccountId) {
-		let nonce = *self.nonce.get(who).unwrap_or(&T::Nonce::zero());
-		self.nonce.insert(who.clone(), nonce + T::Nonce::one());
+		self.nonce.mutate(who.clone(), |nonce| *nonce = *nonce + T::Nonce::one());
+	}
+
+	/// Record an event emitted during the current block.
+	pub fn deposit_event(&mut self, event: T::RuntimeEvent) {
+		self.events.push(event);
+	}
+
+	/// Get the events emitted during the current block, in the order they were collected.
+	pub fn events(&self) -> &[T::RuntimeEvent] {
+		&self.events
+	}
+
+	/// Clear the events of the previous block, and return them.
+	pub fn reset_events(&mut self) -> Vec<T::RuntimeEvent> {
+		core::mem::take(&mut self.events)
+	}
+
+	/// Put back the events of the previous block, once the block which replaced them is rejected.
+	pub fn restore_events(&mut self, events: Vec<T::RuntimeEvent>) {
+		self.events = events;
+	}
+
+	/// Open a transaction on the collected events, so that the events collected from now on can
+	/// be discarded with `rollback_events_transaction`.
+	pub fn start_events_transaction(&mut self) {
+		self.event_marks.push(self.events.len());
 	}
 
-	// Get the nonce of an account.
-	pub fn get_nonce(&self, who: &T::AccountId) -> T::Nonce {
-		*self.nonce.get(who).unwrap_or(&T::Nonce::zero())
+	/// Keep the events collected in the most recent transaction.
+	pub fn commit_events_transaction(&mut self) {
+		self.event_marks.pop();
+	}
+
+	/// Discard the events collected in the most recent transaction.
+	pub fn rollback_events_transaction(&mut self) {
+		if let Some(len) = self.event_marks.pop() {
+			self.events.truncate(len);
+		}
 	}
 }
 
@@ -66,11 +233,12 @@ mod test {
 		type AccountId = String;
         type BlockNumber = u32;
    		type Nonce = u32;
+		type RuntimeEvent = ();
 	}
 
 	#[test]
 	fn init_system() {
-		let mut system: super::Pallet<TestConfig> = super::Pallet::new();
+		let system: super::Pallet<TestConfig> = super::Pallet::new();
         assert_eq!(system.block_number(), 0);
 	}
 
@@ -0,0 +1,286 @@
+use crate::support::{
+	storage::{with_transaction, Transactional},
+	Dispatch, DispatchResult, GetWeight, HasPallet, TakeEvents, Weight, DEFAULT_WEIGHT,
+};
+use core::marker::PhantomData;
+
+pub trait Config: crate::system::Config {
+	/// The overarching call type of the runtime, which batches are made of.
+	type RuntimeCall: GetWeight;
+
+	/// The maximum number of batches which can be nested in each other, including the outermost
+	/// one.
+	const MAX_BATCH_DEPTH: u32 = 4;
+}
+
+/// The events emitted by the utility pallet.
+#[derive(Debug, Clone, PartialEq, Eq, macros::EventMetadata)]
+pub enum Event {
+	/// A `batch` stopped at the call `index`, which failed with `error`. The calls before it were
+	/// executed.
+	BatchInterrupted { index: u32, error: &'static str },
+	/// All the calls of a batch were executed successfully.
+	BatchCompleted,
+	/// All the calls of a `force_batch` were executed, but some of them failed.
+	BatchCompletedWithErrors,
+	/// The call `index` of a `force_batch` failed with `error`.
+	ItemFailed { index: u32, error: &'static str },
+}
+
+/// The errors returned by the utility pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, macros::Error)]
+pub enum Error {
+	/// The batch is nested in `MAX_BATCH_DEPTH` other batches.
+	#[error("Too many nested batches")]
+	TooManyNestedBatches,
+}
+
+/// This is the Utility Module.
+/// It allows an account to bundle several calls to any pallet into a single extrinsic.
+#[macros::storage]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pallet<T: Config> {
+	/// The events emitted since the runtime last collected them.
+	events: Vec<Event>,
+	/// The number of batches the batch being dispatched is nested in.
+	depth: u32,
+	_config: PhantomData<T>,
+}
+
+#[macros::call(explicit_call_index, error = Error)]
+impl<T: Config> Pallet<T>
+where
+	T: Dispatch<Caller = T::AccountId, Call = T::RuntimeCall> + HasPallet<Pallet<T>> + Transactional,
+{
+	/// Dispatch `calls` one after the other on behalf of the `caller`, stopping at the first one
+	/// which fails. The calls before it are not reverted.
+	/// A failing call is reported with a `BatchInterrupted` event instead of failing the batch.
+	/// This function only fails if the batch is nested too deeply.
+	#[call_index(0)]
+	#[weight(Pallet::<T>::batch_weight(calls))]
+	pub fn batch(runtime: &mut T, caller: T::AccountId, calls: Vec<T::RuntimeCall>) -> DispatchResult {
+		Self::ensure_depth(runtime)?;
+		for (index, call) in calls.into_iter().enumerate() {
+			if let Err(error) = Self::dispatch_nested(runtime, caller.clone(), call) {
+				Self::deposit_event(runtime, Event::BatchInterrupted { index: index as u32, error });
+				return Ok(());
+			}
+		}
+		Self::deposit_event(runtime, Event::BatchCompleted);
+		Ok(())
+	}
+
+	/// Dispatch `calls` one after the other on behalf of the `caller`, in a storage transaction.
+	/// Either all the calls succeed, or the transaction is rolled back and the error of the
+	/// failing call is returned.
+	#[call_index(1)]
+	#[weight(Pallet::<T>::batch_weight(calls))]
+	pub fn batch_all(
+		runtime: &mut T,
+		caller: T::AccountId,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		Self::ensure_depth(runtime)?;
+		with_transaction(runtime, |runtime| {
+			calls.into_iter().try_for_each(|call| Self::dispatch_nested(runtime, caller.clone(), call))
+		})?;
+		Self::deposit_event(runtime, Event::BatchCompleted);
+		Ok(())
+	}
+
+	/// Dispatch all of the `calls` on behalf of the `caller`, even if some of them fail.
+	/// Every failing call is reported with an `ItemFailed` event.
+	#[call_index(2)]
+	#[weight(Pallet::<T>::batch_weight(calls))]
+	pub fn force_batch(
+		runtime: &mut T,
+		caller: T::AccountId,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		Self::ensure_depth(runtime)?;
+		let mut failed = false;
+		for (index, call) in calls.into_iter().enumerate() {
+			if let Err(error) = Self::dispatch_nested(runtime, caller.clone(), call) {
+				failed = true;
+				Self::deposit_event(runtime, Event::ItemFailed { index: index as u32, error });
+			}
+		}
+
+		if failed {
+			Self::deposit_event(runtime, Event::BatchCompletedWithErrors);
+		} else {
+			Self::deposit_event(runtime, Event::BatchCompleted);
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: Dispatch<Caller = T::AccountId, Call = T::RuntimeCall> + HasPallet<Pallet<T>>,
+{
+	/// Check that the batch being dispatched is not nested in `MAX_BATCH_DEPTH` other batches.
+	fn ensure_depth(runtime: &T) -> DispatchResult {
+		let utility: &Self = runtime.pallet();
+		if utility.depth >= T::MAX_BATCH_DEPTH {
+			return Err(Error::TooManyNestedBatches.into());
+		}
+		Ok(())
+	}
+
+	/// Dispatch a `call` of the batch through the runtime, so that the batches it contains are
+	/// nested one level deeper.
+	fn dispatch_nested(runtime: &mut T, caller: T::AccountId, call: T::RuntimeCall) -> DispatchResult {
+		let utility: &mut Self = runtime.pallet_mut();
+		utility.depth += 1;
+		let result = runtime.dispatch(caller, call);
+		let utility: &mut Self = runtime.pallet_mut();
+		utility.depth -= 1;
+		result
+	}
+
+	/// Emit an event, to be collected by the runtime.
+	fn deposit_event(runtime: &mut T, event: Event) {
+		let utility: &mut Self = runtime.pallet_mut();
+		utility.events.push(event);
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The weight of a batch of `calls`: the weight of all its calls, on top of the weight of the
+	/// batch itself.
+	pub fn batch_weight(calls: &[T::RuntimeCall]) -> Weight {
+		calls.iter().map(|call| call.weight()).fold(DEFAULT_WEIGHT, Weight::saturating_add)
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T> for Pallet<T> {}
+
+impl<T: Config> TakeEvents for Pallet<T> {
+	type Event = Event;
+
+	fn take_events(&mut self) -> Vec<Event> {
+		core::mem::take(&mut self.events)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Event;
+	use crate::support::Dispatch;
+	use crate::{balances, proof_of_existence, Runtime, RuntimeCall, RuntimeEvent};
+
+	fn new_runtime() -> Runtime {
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime
+	}
+
+	fn transfer(to: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::transfer { to: to.to_string(), amount })
+	}
+
+	fn utility_events(runtime: &Runtime) -> Vec<&Event> {
+		let events = runtime.system.events().iter();
+		events
+			.filter_map(|event| match event {
+				RuntimeEvent::utility(event) => Some(event),
+				_ => None,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn batch_stops_at_first_error() {
+		let mut runtime = new_runtime();
+		let calls = vec![transfer("bob", 30), transfer("bob", 100), transfer("bob", 10)];
+
+		let result = runtime.dispatch("alice".to_string(), RuntimeCall::utility(super::Call::batch { calls }));
+		assert_eq!(result, Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 30);
+		assert_eq!(
+			utility_events(&runtime),
+			vec![&Event::BatchInterrupted { index: 1, error: "Insufficient balance" }]
+		);
+		assert_eq!(
+			runtime.system.events()[0],
+			RuntimeEvent::balances(balances::Event::Transfer {
+				from: "alice".to_string(),
+				to: "bob".to_string(),
+				amount: 30,
+			})
+		);
+	}
+
+	#[test]
+	fn batch_all_reverts_on_error() {
+		let mut runtime = new_runtime();
+		let before = runtime.clone();
+		let calls = vec![transfer("bob", 30), transfer("charlie", 80)];
+
+		let result = runtime.dispatch("alice".to_string(), RuntimeCall::utility(super::Call::batch_all { calls }));
+		assert_eq!(result, Err("Insufficient balance"));
+		assert_eq!(runtime, before, "state and events are reverted");
+
+		let calls = vec![transfer("bob", 30), transfer("charlie", 70)];
+		let result = runtime.dispatch("alice".to_string(), RuntimeCall::utility(super::Call::batch_all { calls }));
+		assert_eq!(result, Ok(()));
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 0);
+		assert_eq!(utility_events(&runtime), vec![&Event::BatchCompleted]);
+	}
+
+	#[test]
+	fn force_batch_reports_failed_calls() {
+		let mut runtime = new_runtime();
+		let calls = vec![transfer("bob", 30), transfer("bob", 100), transfer("bob", 10)];
+
+		let result = runtime.dispatch("alice".to_string(), RuntimeCall::utility(super::Call::force_batch { calls }));
+		assert_eq!(result, Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 40);
+		assert_eq!(
+			utility_events(&runtime),
+			vec![
+				&Event::ItemFailed { index: 1, error: "Insufficient balance" },
+				&Event::BatchCompletedWithErrors,
+			]
+		);
+	}
+
+	#[test]
+	fn nested_batches_dispatch_for_the_same_caller() {
+		let mut runtime = new_runtime();
+		let claim = proof_of_existence::Pallet::<Runtime>::hash_content(b"Batched Document");
+		let inner = vec![
+			transfer("bob", 10),
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim,
+				expires_in: None,
+			}),
+		];
+		let calls = vec![RuntimeCall::utility(super::Call::batch_all { calls: inner })];
+
+		let result = runtime.dispatch("alice".to_string(), RuntimeCall::utility(super::Call::batch { calls }));
+		assert_eq!(result, Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 10);
+		assert_eq!(runtime.proof_of_existence.get_claim(&claim), Some(&"alice".to_string()));
+		assert_eq!(utility_events(&runtime), vec![&Event::BatchCompleted, &Event::BatchCompleted]);
+	}
+
+	#[test]
+	fn batches_nest_up_to_max_depth() {
+		let nested = |depth: u32| {
+			(0..depth).fold(transfer("bob", 10), |call, _| {
+				RuntimeCall::utility(super::Call::batch_all { calls: vec![call] })
+			})
+		};
+		let max_depth = <Runtime as super::Config>::MAX_BATCH_DEPTH;
+		let mut runtime = new_runtime();
+
+		let result = runtime.dispatch("alice".to_string(), nested(max_depth + 1));
+		assert_eq!(result, Err("Too many nested batches"));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+
+		assert_eq!(runtime.dispatch("alice".to_string(), nested(max_depth)), Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 10);
+	}
+}